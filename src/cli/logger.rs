@@ -0,0 +1,30 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// backend for the `log` facade, writing every enabled record to stderr
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// installs the logger: warnings are always shown, and each `-v` unlocks the next level
+pub fn init(verbosity: u8) {
+    let level = match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    log::set_logger(&StderrLogger).expect("logger initialized twice");
+    log::set_max_level(level);
+}
@@ -0,0 +1,278 @@
+pub mod logger;
+
+use crate::config::ColorMode;
+use crate::errors::err::{ErrKind, LanguloErr};
+use crate::lexer::tok::Tok;
+use crate::lexer::Lexer;
+use crate::compilation::compile;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use clap_complete::Shell;
+use codespan_reporting::files::SimpleFile;
+use notify::{EventKind, RecursiveMode, Watcher};
+use rayon::prelude::*;
+use std::io::Write;
+use std::process::ExitCode;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use std::{fs, io, thread};
+
+/// exit status of the process, so that scripts can tell failures apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success = 0,
+    RuntimeError = 1,
+    CompileError = 2,
+    UsageError = 3,
+    IoError = 4,
+}
+
+impl From<&LanguloErr> for Status {
+    fn from(err: &LanguloErr) -> Self {
+        match err.kind {
+            ErrKind::Lexical | ErrKind::Semantic => Status::CompileError,
+            ErrKind::Runtime => Status::RuntimeError,
+        }
+    }
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+pub fn command() -> Command {
+    Command::new("langulo-rs")
+        .version("1.0")
+        .author("Edoardo Finauri")
+        .about("REPL for the Langulo programming language")
+        .arg(Arg::new("eval")
+            .short('e')
+            .long("eval")
+            .value_name("SOURCE")
+            // sources such as `-1` would otherwise be taken for flags
+            .allow_hyphen_values(true)
+            .help("Evaluates SOURCE and prints the result"))
+        .arg(Arg::new("verbose")
+            .short('v')
+            .action(ArgAction::Count)
+            .global(true)
+            .help("Logs more details about what the toolchain is doing (-v, -vv, -vvv)"))
+        .arg(Arg::new("color")
+            .long("color")
+            .value_parser(value_parser!(ColorMode))
+            .global(true)
+            .help("Whether to color the output, overriding langulo.toml"))
+        .arg(Arg::new("timings")
+            .long("timings")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .help("Prints how long each stage of the pipeline took"))
+        .subcommand(Command::new("run")
+            .about("Runs a Langulo program")
+            .arg(Arg::new("file").required(true))
+            .arg(Arg::new("emit-tokens")
+                .long("emit-tokens")
+                .action(ArgAction::SetTrue)
+                .help("Prints the token stream and exits"))
+            .arg(Arg::new("emit-ast")
+                .long("emit-ast")
+                .action(ArgAction::SetTrue)
+                .help("Prints the syntax tree and exits"))
+            .arg(Arg::new("watch")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .help("Runs the program again every time the file changes")))
+        .subcommand(Command::new("check")
+            .about("Reports the diagnostics of Langulo programs without running them")
+            .arg(Arg::new("file").required(true).num_args(1..)))
+        .subcommand(Command::new("completions")
+            .about("Prints the completion script for a shell")
+            .arg(Arg::new("shell")
+                .required(true)
+                .value_parser(value_parser!(Shell))))
+}
+
+/// parses the command line, reporting malformed invocations as usage errors
+/// (clap's own convention would exit with the status reserved for compile errors)
+pub fn matches() -> Result<ArgMatches, Status> {
+    command().try_get_matches().map_err(|err| {
+        let _ = err.print();
+        if err.use_stderr() { Status::UsageError } else { Status::Success }
+    })
+}
+
+fn read_source(path: &str) -> Result<String, Status> {
+    fs::read_to_string(path).map_err(|err| {
+        eprintln!("Could not read {path}: {err}");
+        Status::IoError
+    })
+}
+
+fn report(err: LanguloErr, sf: &SimpleFile<&str, &String>) -> Status {
+    err.emit(sf);
+    Status::from(&err)
+}
+
+/// reports every diagnostic, with the status of the first one
+fn report_all(diagnostics: &[LanguloErr], sf: &SimpleFile<&str, &String>) -> Status {
+    diagnostics.iter().for_each(|err| err.emit(sf));
+    diagnostics.first().map_or(Status::Success, Status::from)
+}
+
+pub fn run(args: &ArgMatches) -> Status {
+    let path = args.get_one::<String>("file").unwrap();
+    if !args.get_flag("watch") {
+        return run_file(path, args);
+    }
+    match watch(path, || { run_file(path, args); }) {
+        Ok(()) => Status::Success,
+        Err(err) => {
+            eprintln!("Could not watch {path}: {err}");
+            Status::IoError
+        }
+    }
+}
+
+fn run_file(path: &str, args: &ArgMatches) -> Status {
+    log::info!("running {path}");
+    let source = match read_source(path) {
+        Ok(source) => source,
+        Err(status) => return status,
+    };
+    let sf = SimpleFile::new(path, &source);
+
+    if args.get_flag("emit-tokens") {
+        let printed = emit_tokens_to(&source, |tok, content| println!("{tok:?} {content:?}"));
+        return printed.map_or_else(|err| report(err, &sf), |()| Status::Success);
+    }
+
+    evaluate(&sf, args, args.get_flag("emit-ast"))
+}
+
+/// there is no backend yet, so the result of a snippet is its syntax tree, as in the REPL
+pub fn eval(source: &String, args: &ArgMatches) -> Status {
+    evaluate(&SimpleFile::new("<eval>", source), args, true)
+}
+
+/// compiles a program, printing its syntax tree if `emit_ast` is set.
+/// there is no backend to run it on yet, so otherwise this is only a check
+fn evaluate(sf: &SimpleFile<&str, &String>, args: &ArgMatches, emit_ast: bool) -> Status {
+    let timings = args.get_flag("timings");
+    if timings {
+        // the parser lexes on demand, so lexing is timed on a pass of its own
+        let start = Instant::now();
+        let _ = emit_tokens_to(sf.source(), |_, _| {});
+        eprintln!("{:<7} {:?}", "lex", start.elapsed());
+    }
+
+    let result = compile(sf.source());
+    for (stage, elapsed) in &result.timings {
+        log::debug!("{stage} took {elapsed:?} on {} bytes", sf.source().len());
+        if timings { eprintln!("{stage:<7} {elapsed:?}"); }
+    }
+    match result.ast {
+        Some(ast) if result.is_ok() => {
+            if emit_ast {
+                println!("{:#?}", ast);
+            } else {
+                log::warn!("{} has no errors, but there's no backend to run it yet", sf.name());
+            }
+            Status::Success
+        }
+        _ => report_all(&result.diagnostics, sf),
+    }
+}
+
+/// checks every file on a thread pool. the diagnostics are reported in the order the files
+/// were given, and the status is the one of the first file that failed.
+pub fn check(args: &ArgMatches) -> Status {
+    let paths: Vec<&String> = args.get_many::<String>("file").unwrap().collect();
+    // syntax trees can't move across threads, so each file is reduced to its diagnostics where it's checked
+    let checked: Vec<_> = paths.par_iter()
+        .map(|path| fs::read_to_string(path).map(|source| {
+            let diagnostics = compile(&source).diagnostics;
+            (source, diagnostics)
+        }))
+        .collect();
+
+    let mut status = Status::Success;
+    for (path, checked) in paths.iter().zip(checked) {
+        let file_status = match checked {
+            Ok((source, diagnostics)) => report_all(&diagnostics, &SimpleFile::new(path.as_str(), &source)),
+            Err(err) => {
+                eprintln!("Could not read {path}: {err}");
+                Status::IoError
+            }
+        };
+        if status == Status::Success { status = file_status; }
+    }
+    status
+}
+
+pub fn completions(args: &ArgMatches) -> Status {
+    let shell = *args.get_one::<Shell>("shell").unwrap();
+    clap_complete::generate(shell, &mut command(), "langulo", &mut io::stdout());
+    Status::Success
+}
+
+/// calls `on_change` on a cleared screen, then again every time the file at `path` is written.
+/// the parent directory is watched instead of the file, since many editors save by replacing it.
+fn watch(path: &str, mut on_change: impl FnMut()) -> notify::Result<()> {
+    let file = fs::canonicalize(path)?;
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(file.parent().unwrap_or(&file), RecursiveMode::NonRecursive)?;
+
+    loop {
+        print!("\x1B[2J\x1B[1;1H");
+        io::stdout().flush()?;
+        on_change();
+
+        loop {
+            let Ok(event) = rx.recv() else { return Ok(()) };
+            let event = event?;
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event.paths.contains(&file) { break; }
+        }
+        log::info!("{path} changed");
+        // a single save usually comes as a burst of events
+        thread::sleep(Duration::from_millis(50));
+        while rx.try_recv().is_ok() {}
+    }
+}
+
+fn emit_tokens_to(source: &str, mut emit: impl FnMut(Tok, &str)) -> Result<(), LanguloErr> {
+    let mut lexer = Lexer::new(source);
+    while let Some((tok, content)) = lexer.next()? {
+        emit(tok, content);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_statuses() {
+        let statuses = [
+            (Status::Success, 0),
+            (Status::RuntimeError, 1),
+            (Status::CompileError, 2),
+            (Status::UsageError, 3),
+            (Status::IoError, 4),
+        ];
+        for (status, code) in statuses {
+            assert_eq!(ExitCode::from(status), ExitCode::from(code), "wrong exit code for {status:?}");
+        }
+    }
+
+    #[test]
+    fn error_statuses() {
+        assert_eq!(Status::from(&LanguloErr::lexical("bad token", &(0..1))), Status::CompileError);
+        assert_eq!(Status::from(&LanguloErr::semantic("bad tree")), Status::CompileError);
+        assert_eq!(Status::from(&LanguloErr::semantic_at("bad tree", &(0..1))), Status::CompileError);
+        assert_eq!(Status::from(&LanguloErr::_runtime("bad value", &(0..1))), Status::RuntimeError);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::errors::err::LanguloErr;
+use crate::parser::Parser;
+use crate::resolver::{self, SymbolTable};
+use crate::syntax_tree::lang::LanguloSyntaxNode;
+use std::time::{Duration, Instant};
+
+/// everything the pipeline produced for a source, so that the CLI, the REPL and
+/// other tooling can inspect it without running the stages again
+#[derive(Debug, Default)]
+pub struct CompilationResult {
+    /// the lossless syntax tree, which is missing if the source didn't parse
+    pub ast: Option<LanguloSyntaxNode>,
+    /// what each variable refers to, which is missing if the source didn't resolve
+    pub symbols: Option<SymbolTable>,
+    pub diagnostics: Vec<LanguloErr>,
+    /// how long each stage that ran took, in order
+    pub timings: Vec<(&'static str, Duration)>,
+}
+
+impl CompilationResult {
+    pub fn is_ok(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    fn timed<T>(&mut self, stage: &'static str, run: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let output = run();
+        self.timings.push((stage, start.elapsed()));
+        output
+    }
+}
+
+/// runs the source through every stage, stopping at the first one that fails
+pub fn compile(source: &str) -> CompilationResult {
+    let mut result = CompilationResult::default();
+
+    let mut parser = Parser::new(source);
+    if let Err(err) = result.timed("parse", || parser.parse()) {
+        result.diagnostics.push(err);
+        return result;
+    }
+    let ast = parser.into_ast();
+
+    match result.timed("resolve", || resolver::resolve(&ast)) {
+        Ok(symbols) => {
+            log::debug!("resolved {} uses of {} declarations", symbols.uses.len(), symbols.declarations.len());
+            result.symbols = Some(symbols);
+        }
+        Err(err) => result.diagnostics.push(err),
+    }
+    result.ast = Some(ast);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages() {
+        let result = compile("var x = 1; x + 1");
+        assert!(result.is_ok());
+        assert!(result.ast.is_some_and(|ast| ast.text() == "var x = 1; x + 1"));
+        assert!(result.symbols.is_some_and(|symbols| symbols.uses.len() == 1));
+        let stages: Vec<_> = result.timings.iter().map(|(stage, _)| *stage).collect();
+        assert_eq!(stages, ["parse", "resolve"]);
+
+        let unparsed = compile("1 +");
+        assert_eq!(unparsed.diagnostics.len(), 1);
+        assert!(unparsed.ast.is_none());
+        assert_eq!(unparsed.timings.len(), 1);
+
+        let unresolved = compile("x + 1");
+        assert_eq!(unresolved.diagnostics.len(), 1);
+        assert!(unresolved.ast.is_some());
+        assert!(unresolved.symbols.is_none());
+    }
+}
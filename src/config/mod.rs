@@ -0,0 +1,107 @@
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+const FILE_NAME: &str = "langulo.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+/// defaults for the CLI and the REPL, read from a `langulo.toml` in the working directory,
+/// or else in the home directory. command line flags take precedence over it.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub color: Option<ColorMode>,
+    pub repl: ReplConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReplConfig {
+    /// where the REPL persists its history; it isn't kept across sessions if unset
+    pub history: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let Some(path) = Self::find() else { return Self::default() };
+        log::debug!("reading configuration from {}", path.display());
+        let parsed = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| Self::parse(&content, home_dir().as_deref()).map_err(|err| err.to_string()));
+        parsed.unwrap_or_else(|err| {
+            log::warn!("ignoring {}: {err}", path.display());
+            Self::default()
+        })
+    }
+
+    /// parses the content of a config file. paths starting with `~` are taken from `home`
+    fn parse(content: &str, home: Option<&Path>) -> Result<Self, toml::de::Error> {
+        let mut config: Self = toml::from_str(content)?;
+        if let (Some(history), Some(home)) = (&config.repl.history, home) {
+            if let Ok(relative) = history.strip_prefix("~") {
+                config.repl.history = Some(home.join(relative));
+            }
+        }
+        Ok(config)
+    }
+
+    fn find() -> Option<PathBuf> {
+        let local = PathBuf::from(FILE_NAME);
+        if local.is_file() { return Some(local); }
+        let home = home_dir()?.join(FILE_NAME);
+        home.is_file().then_some(home)
+    }
+
+    pub fn merge_args(mut self, args: &ArgMatches) -> Self {
+        if let Some(color) = args.get_one::<ColorMode>("color") {
+            self.color = Some(*color);
+        }
+        self
+    }
+
+    /// applies the settings that are global to the process
+    pub fn apply(&self) {
+        match self.color {
+            Some(ColorMode::Always) => colored::control::set_override(true),
+            Some(ColorMode::Never) => colored::control::set_override(false),
+            Some(ColorMode::Auto) | None => {}
+        }
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let home = Path::new("/home/someone");
+        let config = Config::parse(r#"
+            color = "never"
+            [repl]
+            history = "~/.langulo_history"
+        "#, Some(home)).expect("failed to parse config");
+        assert_eq!(config.color, Some(ColorMode::Never));
+        assert_eq!(config.repl.history, Some(PathBuf::from("/home/someone/.langulo_history")));
+
+        let config = Config::parse(r#"repl.history = "~user/history""#, Some(home))
+            .expect("failed to parse config");
+        assert_eq!(config.repl.history, Some(PathBuf::from("~user/history")));
+
+        let empty = Config::parse("", Some(home)).expect("failed to parse empty config");
+        assert_eq!(empty.color, None);
+        assert!(Config::parse(r#"color = "sometimes""#, Some(home)).is_err());
+    }
+}
@@ -1,23 +1,48 @@
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::files::SimpleFile;
 use codespan_reporting::term;
-use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use codespan_reporting::term::termcolor::{ColorChoice, StandardStream, WriteColor};
 use logos::Span;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrKind {
+    Lexical,
+    Semantic,
+    Runtime,
+}
 
 #[derive(Debug)]
 pub struct LanguloErr {
+    pub kind: ErrKind,
     diagnostic: Diagnostic<()>,
 }
 
 impl LanguloErr {
     pub fn emit(&self, file: &SimpleFile<&str, &String>) {
-        let writer = StandardStream::stderr(ColorChoice::Always);
+        // follows the same switch as the rest of the colored output
+        let choice = if colored::control::SHOULD_COLORIZE.should_colorize() {
+            ColorChoice::Always
+        } else {
+            ColorChoice::Never
+        };
+        let writer = StandardStream::stderr(choice);
+        self.emit_to(&mut writer.lock(), file);
+    }
+
+    pub fn emit_to(&self, writer: &mut dyn WriteColor, file: &SimpleFile<&str, &String>) {
+        let source = file.source().as_str();
+        let mut diagnostic = self.diagnostic.clone();
+        for label in &mut diagnostic.labels {
+            label.range = char_boundaries(source, &label.range);
+        }
         let config = codespan_reporting::term::Config::default();
-        term::emit(&mut writer.lock(), &config, file, &self.diagnostic)
+        term::emit(writer, &config, file, &diagnostic)
             .expect("failed to write diagnostic");
     }
     pub fn lexical(msg: &str, span: &Span) -> Self {
         Self {
+            kind: ErrKind::Lexical,
             diagnostic: Diagnostic::error()
                 .with_message(format!("LexicalError - {msg}"))
                 .with_labels(vec![Label::primary((),
@@ -27,17 +52,101 @@ impl LanguloErr {
 
     pub fn _runtime(msg: &str, span: &Span) -> Self {
         Self {
+            kind: ErrKind::Runtime,
             diagnostic: Diagnostic::error()
                 .with_message(format!("RuntimeError - {msg}"))
                 .with_labels(vec![Label::primary((), span.start..span.end)]),
         }
     }
 
+    pub fn semantic_at(msg: &str, span: &Span) -> Self {
+        Self {
+            kind: ErrKind::Semantic,
+            diagnostic: Diagnostic::error()
+                .with_message(format!("SemanticError - {msg}"))
+                .with_labels(vec![Label::primary((), span.start..span.end)]),
+        }
+    }
+
     pub fn semantic(msg: &str, /*span: &Span*/) -> Self {
         Self {
+            kind: ErrKind::Semantic,
             diagnostic: Diagnostic::error()
                 .with_message(format!("SemanticError - {msg}"))
                 .with_labels(vec![Label::primary((), 0..0)]),
         }
     }
-}
\ No newline at end of file
+
+    /// points to another place in the source that explains the error
+    pub fn with_note_at(mut self, msg: &str, span: &Span) -> Self {
+        self.diagnostic.labels.push(Label::secondary((), span.start..span.end).with_message(msg));
+        self
+    }
+
+    /// where the error is reported, in bytes
+    #[cfg(test)]
+    pub fn span(&self) -> Span {
+        self.diagnostic.labels.first().map_or(0..0, |label| label.range.clone())
+    }
+
+    /// moves the labels by `offset` bytes, for a source that is part of a larger file
+    pub fn shifted(mut self, offset: usize) -> Self {
+        for label in &mut self.diagnostic.labels {
+            label.range = label.range.start + offset..label.range.end + offset;
+        }
+        self
+    }
+}
+
+/// spans are in bytes: widens the range so that it doesn't cut through a multi-byte character,
+/// and clamps it to the source so that labels past the end still render.
+fn char_boundaries(source: &str, range: &Range<usize>) -> Range<usize> {
+    let mut start = range.start.min(source.len());
+    while !source.is_char_boundary(start) { start -= 1; }
+    let mut end = range.end.clamp(start, source.len());
+    while !source.is_char_boundary(end) { end += 1; }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codespan_reporting::term::termcolor::Buffer;
+
+    fn render(source: &str, err: LanguloErr) -> String {
+        let source = source.to_string();
+        let file = SimpleFile::new("test", &source);
+        let mut buffer = Buffer::no_color();
+        err.emit_to(&mut buffer, &file);
+        String::from_utf8(buffer.into_inner()).expect("diagnostic isn't valid utf-8")
+    }
+
+    #[test]
+    fn char_boundaries_widen_ranges() {
+        let source = "\"🦀\" + δ";
+        assert_eq!(char_boundaries(source, &(2..3)), 1..5);
+        assert_eq!(char_boundaries(source, &(10..11)), 9..11);
+        assert_eq!(char_boundaries(source, &(50..60)), 11..11);
+    }
+
+    #[test]
+    fn emit_non_ascii() {
+        let rendered = render("\"🦀\" + δ", LanguloErr::semantic_at("bad operand", &(2..3)));
+        assert!(rendered.contains("SemanticError - bad operand"));
+        assert!(rendered.contains("🦀"));
+        let rendered = render("café", LanguloErr::lexical("unexpected end", &(4..9)));
+        assert!(rendered.contains("LexicalError - unexpected end"));
+    }
+
+    #[test]
+    fn notes() {
+        let err = LanguloErr::semantic_at("declared twice", &(6..7)).with_note_at("first here", &(0..1));
+        assert!(render("x = 1; x = 2", err).contains("first here"));
+    }
+
+    #[test]
+    fn shifted_labels() {
+        let err = LanguloErr::semantic_at("undeclared", &(0..1)).shifted(6);
+        assert!(render("var x\ny + 1", err).contains("test:2:1"));
+    }
+}
@@ -1,4 +1,4 @@
-use logos::Logos;
+use logos::{Logos, Span};
 use crate::errors::err::LanguloErr;
 use crate::lexer::tok::Tok;
 
@@ -8,13 +8,21 @@ pub mod tok;
 pub struct Lexer<'a> {
     logos: logos::Lexer<'a, Tok>,
     buffer: Option<(Tok, &'a str)>,
+    buffer_span: Span,
+    /// span of the last token returned by `next`
+    span: Span,
+    /// content of the last token returned by `next`
+    last: &'a str,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
             logos: Tok::lexer(input),
-            buffer: None
+            buffer: None,
+            buffer_span: Span::default(),
+            span: Span::default(),
+            last: "",
         }
     }
 
@@ -31,16 +39,35 @@ impl<'a> Lexer<'a> {
 
     pub fn next(&mut self) -> Result<Option<(Tok, &'a str)>, LanguloErr> {
         if let Some(buf) = self.buffer.take() {
-            self.buffer = None;
+            self.span = self.buffer_span.clone();
+            self.last = buf.1;
             return Ok(Some(buf));
         }
-        self.inner_next()
+        let next = self.inner_next()?;
+        self.span = self.logos.span();
+        if let Some((_, content)) = next { self.last = content; }
+        Ok(next)
+    }
+
+    pub fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    pub fn last(&self) -> &'a str {
+        self.last
     }
 
     pub fn peek(&mut self) -> Result<&Option<(Tok, &'a str)>, LanguloErr> {
         if self.buffer.is_none() {
-            self.buffer = self.inner_next()?
+            self.buffer = self.inner_next()?;
+            self.buffer_span = self.logos.span();
         }
         Ok(&self.buffer)
     }
+
+    /// span of the token that the next call to `next` returns
+    pub fn peek_span(&mut self) -> Result<Span, LanguloErr> {
+        self.peek()?;
+        Ok(self.buffer_span.clone())
+    }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use logos::Logos;
+use num_derive::FromPrimitive;
 
-#[derive(Debug, PartialEq, Logos)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, Logos)]
 pub enum Tok {
     // this enum doesn't store the token payload.
     // instead, we carry the token's value as a slice on the input program's string.
@@ -18,6 +19,7 @@ pub enum Tok {
     // punctuation
     #[regex(",")]           Comma,
     #[regex(";")]           Semicolon,
+    #[regex(":")]           Colon,
     #[regex("\\.")]         Dot,
     #[regex("\\|")]         Pipe,
 
@@ -40,9 +42,11 @@ pub enum Tok {
 
     // primitive values
     #[regex("true|false")]  Bool,
-    #[regex(r"-?\d+")]      Int,
-    #[regex(r"-?\d+\.\d+")] Float,
+    #[regex(r"\d+")]        Int,
+    #[regex(r"\d+\.\d+")]   Float,
     #[regex(r#""(?:[^"]|\\")*""#)] String,
+    // escapes are kept verbatim, so a raw string ends at the first `"`
+    #[regex(r#"r"[^"]*""#)] RawString,
     #[regex(r"'.'")]        Char,
 
     // arithmetic
@@ -53,6 +57,14 @@ pub enum Tok {
     #[regex("\\^")]         Caret,
     #[regex("%")]           Modulo,
 
+    // bitwise, in keyword form since `|` already delimits lambdas
+    #[regex("band")]        BitAnd,
+    #[regex("bor")]         BitOr,
+    #[regex("bxor")]        BitXor,
+    #[regex("bnot")]        BitNot,
+    #[regex("shl")]         ShiftLeft,
+    #[regex("shr")]         ShiftRight,
+
     // logic
     #[regex("not")]         Not,
     #[regex("and")]         And,
@@ -68,6 +80,8 @@ pub enum Tok {
     // optionals, tables
     #[regex("if")]          If,
     #[regex("else")]        Else,
+    #[regex("while")]       While,
+    #[regex("match")]       Match,
     #[regex("\\?")]         Question,
     #[regex("no")]          No,
     #[regex("!")]           Bang,
@@ -76,20 +90,26 @@ pub enum Tok {
     #[regex("list")]        List,
     #[regex("set")]         Set,
     #[regex("\\.\\.")]      Range,
+    #[regex("\\.\\.\\.")]   Ellipsis,
 
     // functions and structs
     #[regex("fn")]          Fn,
     #[regex("@")]           At,
+    #[regex("->")]          Arrow,
     #[regex("return")]      Return,
     #[regex("struct")]      Struct,
     #[regex("new")]         New,
 
+    // testing
+    #[regex("test")]        Test,
+
     // others
     #[regex("\\$")]         Dollar,     // print
     #[regex("as")]          As,         // cast
+    #[regex("typeof")]      TypeOf,
     #[regex("\\s+")]        Whitespace,
     #[regex("//.*")]        Comment,
-    #[regex("[A-Za-z][a-zA-Z_]*")] Identifier,
+    #[regex(r"\p{XID_Start}\p{XID_Continue}*")] Identifier,
 }
 
 #[cfg(test)]
@@ -101,8 +121,8 @@ mod tests {
         for (i, tok) in toks.iter().enumerate() {
             println!("iteration: {i}, expecting {tok:?}");
             assert_eq!(&lexer.next()
-                .expect(format!("expected {tok:?}, but lexer has nothing to parse").as_str())
-                .expect(format!("lexing error when expecting tok {tok:?}").as_str()),
+                .unwrap_or_else(|| panic!("expected {tok:?}, but lexer has nothing to parse"))
+                .unwrap_or_else(|_| panic!("lexing error when expecting tok {tok:?}")),
                        tok);
         }
         assert!(&lexer.next().is_none())
@@ -120,13 +140,15 @@ mod tests {
 
     #[test]
     fn punctuation_and_others() {
-        expect_lex(",;.|$ as hello", &[
+        expect_lex(",;:.|$ as typeof hello", &[
             Tok::Comma,
             Tok::Semicolon,
+            Tok::Colon,
             Tok::Dot,
             Tok::Pipe,
             Tok::Dollar, Tok::Whitespace,
             Tok::As, Tok::Whitespace,
+            Tok::TypeOf, Tok::Whitespace,
             Tok::Identifier]);
     }
 
@@ -164,7 +186,16 @@ mod tests {
             Tok::Char, Tok::Whitespace,
             Tok::Bool, Tok::Whitespace,
             Tok::Bool, Tok::Whitespace,
-            Tok::Int, Tok::Whitespace,
+            Tok::Minus, Tok::Int, Tok::Whitespace,
+        ]);
+    }
+
+    #[test]
+    fn raw_strings() {
+        expect_lex("r\"a\\d+\n\\\" r \"r\"", &[
+            Tok::RawString, Tok::Whitespace,
+            Tok::Identifier, Tok::Whitespace,
+            Tok::String,
         ]);
     }
 
@@ -189,9 +220,31 @@ mod tests {
         ])
     }
 
+    #[test]
+    fn bitwise() {
+        expect_lex("band bor bxor bnot shl shr bandit", &[
+            Tok::BitAnd, Tok::Whitespace,
+            Tok::BitOr, Tok::Whitespace,
+            Tok::BitXor, Tok::Whitespace,
+            Tok::BitNot, Tok::Whitespace,
+            Tok::ShiftLeft, Tok::Whitespace,
+            Tok::ShiftRight, Tok::Whitespace,
+            Tok::Identifier,
+        ])
+    }
+
+    #[test]
+    fn loops() {
+        expect_lex("while whiles match", &[
+            Tok::While, Tok::Whitespace,
+            Tok::Identifier, Tok::Whitespace,
+            Tok::Match,
+        ])
+    }
+
     #[test]
     fn options_and_tables() {
-        expect_lex("if else ? no ! _ iter list set .. ", &[
+        expect_lex("if else ? no ! _ iter list set .. ... ", &[
             Tok::If, Tok::Whitespace,
             Tok::Else, Tok::Whitespace,
             Tok::Question, Tok::Whitespace,
@@ -202,20 +255,43 @@ mod tests {
             Tok::List, Tok::Whitespace,
             Tok::Set, Tok::Whitespace,
             Tok::Range, Tok::Whitespace,
+            Tok::Ellipsis, Tok::Whitespace,
         ])
     }
 
     #[test]
     fn fn_and_structs() {
-        expect_lex("fn @ return struct new ", &[
+        expect_lex("fn @ -> return struct new ", &[
             Tok::Fn, Tok::Whitespace,
             Tok::At, Tok::Whitespace,
+            Tok::Arrow, Tok::Whitespace,
             Tok::Return, Tok::Whitespace,
             Tok::Struct, Tok::Whitespace,
             Tok::New, Tok::Whitespace,
         ])
     }
 
+    #[test]
+    fn testing() {
+        expect_lex("test tests", &[
+            Tok::Test, Tok::Whitespace,
+            Tok::Identifier,
+        ])
+    }
+
+    #[test]
+    fn identifiers() {
+        expect_lex("café1 δ snake_case2 x2y var2 if", &[
+            Tok::Identifier, Tok::Whitespace,
+            Tok::Identifier, Tok::Whitespace,
+            Tok::Identifier, Tok::Whitespace,
+            Tok::Identifier, Tok::Whitespace,
+            Tok::Identifier, Tok::Whitespace,
+            Tok::If,
+        ]);
+        expect_lex("2x", &[Tok::Int, Tok::Identifier]);
+    }
+
     #[test]
     fn errors() {
         for input in &[
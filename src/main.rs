@@ -1,16 +1,36 @@
-use clap::Command;
-
 mod lexer;
 mod parser;
 mod repl;
+mod resolver;
 mod errors;
 mod syntax_tree;
+mod cli;
+mod compilation;
+mod config;
+
+use std::process::ExitCode;
+use cli::Status;
+use config::Config;
 
-fn main() {
-    let _matches = Command::new("langulo-rs")
-        .version("1.0")
-        .author("Edoardo Finauri")
-        .about("REPL for the Langulo programming language")
-        .get_matches();
-    repl::serve_repl();
+fn main() -> ExitCode {
+    let matches = match cli::matches() {
+        Ok(matches) => matches,
+        Err(status) => return status.into(),
+    };
+    cli::logger::init(matches.get_count("verbose"));
+    let config = Config::load().merge_args(&matches);
+    config.apply();
+    if let Some(source) = matches.get_one::<String>("eval") {
+        return cli::eval(source, &matches).into();
+    }
+    let status = match matches.subcommand() {
+        Some(("run", args)) => cli::run(args),
+        Some(("check", args)) => cli::check(args),
+        Some(("completions", args)) => cli::completions(args),
+        _ => {
+            repl::serve_repl(&config);
+            Status::Success
+        }
+    };
+    status.into()
 }
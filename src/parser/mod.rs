@@ -5,19 +5,48 @@ use crate::lexer::tok::Tok;
 use crate::lexer::Lexer;
 use crate::syntax_tree::expr::Expr;
 use crate::syntax_tree::lang::LanguloSyntaxNode;
+use logos::Span;
 use rowan::Checkpoint;
 
+/// prefix operators bind tighter than any infix arithmetic but `^`, so `-x ^ 2` is `-(x ^ 2)`
+const PREFIX_PRECEDENCE: u8 = 30;
+
+/// how a parameter is filled by the arguments of a call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamKind {
+    Required,
+    Defaulted,
+    Variadic,
+}
+
 pub type ASTBuilder = rowan::GreenNodeBuilder<'static>;
 
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     builder: ASTBuilder,
+    /// whether a newline can stand in for the `;` after a complete statement
+    infer_semicolons: bool,
+    /// whether a newline was skipped since the last significant token
+    newline: bool,
+    /// how many brackets and parens are open; newlines never end a statement inside them
+    nesting: u32,
+    /// the last significant token consumed
+    last_tok: Option<Tok>,
+    /// whether the operand being parsed directly follows a `-`
+    negated: bool,
+    /// the nesting of the body of a lambda being applied with `@`, which ends at the next `@`
+    applied_body: Option<u32>,
+    /// trivia that was skipped but not yet attached. it's only attached right before
+    /// the next element, so that trailing trivia never ends up inside a finished node
+    trivia: Vec<(Tok, &'a str)>,
 }
 
 // macro to avoid double mut borrow
 macro_rules! next {
     ($self:expr) => {{
         let result = $self.lexer.next()?.ok_or_else(|| LanguloErr::semantic("Unexpected EOF"))?;
+        $self.newline = false;
+        $self.last_tok = Some(result.0);
         result
     }};
 }
@@ -27,99 +56,837 @@ impl<'a> Parser<'a> {
         Self {
             lexer: Lexer::new(input),
             builder: ASTBuilder::new(),
+            infer_semicolons: false,
+            newline: false,
+            nesting: 0,
+            last_tok: None,
+            negated: false,
+            applied_body: None,
+            trivia: Vec::new(),
         }
     }
 
-    pub fn to_ast(self) -> LanguloSyntaxNode {
+    /// lets newlines terminate statements, which is convenient in the REPL.
+    /// files keep mandatory semicolons.
+    pub fn infer_semicolons(mut self, enabled: bool) -> Self {
+        self.infer_semicolons = enabled;
+        self
+    }
+
+    pub fn into_ast(self) -> LanguloSyntaxNode {
         LanguloSyntaxNode::new_root(self.builder.finish())
     }
 
-    fn new_leaf_node(&mut self, expr: Expr, content: &str) -> Result<(), LanguloErr> {
+    fn flush_trivia(&mut self) {
+        for (tok, content) in std::mem::take(&mut self.trivia) {
+            self.builder.token(tok.into(), content);
+        }
+    }
+
+    fn token(&mut self, tok: Tok, content: &str) {
+        self.flush_trivia();
+        self.builder.token(tok.into(), content);
+    }
+
+    fn start_node(&mut self, expr: Expr) {
+        self.flush_trivia();
         self.builder.start_node(expr.into());
-        self.builder.token(expr.into(), content);
+    }
+
+    fn new_leaf_node(&mut self, expr: Expr, tok: Tok, content: &str) -> Result<(), LanguloErr> {
+        self.start_node(expr);
+        self.token(tok, content);
         self.builder.finish_node();
         Ok(())
     }
 
-    fn new_binary_node(&mut self, content: &str, checkpoint: Checkpoint, precedence: u8) -> Result<(), LanguloErr> {
+    fn new_binary_node(&mut self, tok: Tok, content: &str, checkpoint: Checkpoint, precedence: u8) -> Result<(), LanguloErr> {
         self.builder.start_node_at(checkpoint, Expr::Binary.into());
-        self.builder.token(Expr::Binary.into(), content);
+        self.token(tok, content);
         self.parse_expr(precedence)?;
         self.builder.finish_node();
         Ok(())
     }
 
-    fn new_unary_node(&mut self, content: &str, checkpoint: Checkpoint, precedence: u8) -> Result<(), LanguloErr> {
-        self.builder.start_node_at(checkpoint, Expr::Unary.into());
-        self.builder.token(Expr::Unary.into(), content);
-        self.parse_expr(precedence)?;
-        self.builder.finish_node();
+    /// consumes the next token, which must be `expected`, and attaches it to the tree
+    fn consume(&mut self, expected: Tok) -> Result<(), LanguloErr> {
+        self.skip_trivia()?;
+        let (tok, content) = next!(self);
+        if tok != expected {
+            return Err(LanguloErr::semantic(
+                &format!("Expected {:?}, but found {}", expected, content)
+            ));
+        }
+        self.token(tok, content);
         Ok(())
     }
 
+    fn peek_tok(&mut self) -> Result<Option<Tok>, LanguloErr> {
+        self.skip_trivia()?;
+        Ok(self.lexer.peek()?.as_ref().map(|(tok, _)| *tok))
+    }
+
     pub fn parse(&mut self) -> Result<(), LanguloErr> {
         self.builder.start_node(Expr::Root.into());
-        self.parse_expr(0)?;
+        self.parse_statements(None)?;
+        self.flush_trivia();
         self.builder.finish_node();
         Ok(())
     }
 
+    /// parses the statements of a block up to `close`, or up to EOF when it's `None`
+    fn parse_statements(&mut self, close: Option<Tok>) -> Result<(), LanguloErr> {
+        while self.peek_tok()? != close {
+            // stray semicolons are empty statements, which are allowed anywhere
+            if self.peek_tok()? == Some(Tok::Semicolon) {
+                self.consume(Tok::Semicolon)?;
+                continue;
+            }
+            self.parse_expr(0)?;
+            self.handle_semicolon(close)?;
+        }
+        Ok(())
+    }
+
+    /// every statement but the last of its block must be followed by a `;`,
+    /// unless it ends with a `}`. when semicolons are inferred,
+    /// a newline after a complete statement works as well.
+    fn handle_semicolon(&mut self, close: Option<Tok>) -> Result<(), LanguloErr> {
+        let next = self.peek_tok()?;
+        if next == Some(Tok::Semicolon) {
+            return self.consume(Tok::Semicolon);
+        }
+        if next == close
+            || self.last_tok == Some(Tok::RBrace)
+            || (self.infer_semicolons && self.newline) {
+            return Ok(());
+        }
+        let found = self.lexer.peek()?.as_ref().map_or("EOF", |(_, content)| *content);
+        Err(LanguloErr::semantic(&format!("Expected ; between expressions, but found {}", found)))
+    }
+
+    fn newline_ends_statement(&self) -> bool {
+        self.infer_semicolons && self.newline && self.nesting == 0
+    }
+
     pub fn parse_expr(&mut self, precedence: u8) -> Result<(), LanguloErr> {
         self.skip_trivia()?;
+        self.flush_trivia();
         let checkpoint = self.builder.checkpoint();
 
+        let assignable = self.peek_tok()? == Some(Tok::Identifier);
         self.parse_prefix()?;
+        self.parse_infix(checkpoint, precedence, assignable)
+    }
 
+    /// parses the operators following an operand, which is already in the tree at `checkpoint`.
+    /// `assignable` tells whether the operand can be the target of an assignment.
+    fn parse_infix(&mut self, checkpoint: Checkpoint, precedence: u8, mut assignable: bool) -> Result<(), LanguloErr> {
         loop {
             self.skip_trivia()?;
-            let tok_precedence = match self.lexer.peek()? {
-                Some((tok, _)) => tok.precedence(),
+            let (tok_precedence, is_index, continues_line) = match self.lexer.peek()? {
+                Some((Tok::At, _)) if self.applied_body == Some(self.nesting) => break,
+                Some((tok, _)) => (
+                    tok.precedence(),
+                    matches!(tok, Tok::LBracket | Tok::Dot),
+                    // a line starting with `@` or `.` carries on a pipeline: `xs\n  @map(f)`
+                    matches!(tok, Tok::At | Tok::Dot),
+                ),
                 None => break,
             };
+            if self.newline_ends_statement() && !continues_line { break; }
             if tok_precedence <= precedence { break; }
 
-            self.parse_postfix(checkpoint, tok_precedence)?;
+            self.parse_postfix(checkpoint, tok_precedence, assignable)?;
+            // of all the operators, only indexing and field access yield something that can be assigned to
+            assignable = is_index;
         }
         Ok(())
     }
 
-    fn parse_postfix(&mut self, checkpoint: Checkpoint, precedence: u8) -> Result<(), LanguloErr> {
+    fn parse_postfix(&mut self, checkpoint: Checkpoint, precedence: u8, assignable: bool) -> Result<(), LanguloErr> {
         let (tok, content) = next!(self);
 
         match tok {
+            // right associative, like `^`. a compound assignment `t[k] += 1` keeps its operator
+            // in the Assign node: the key is evaluated once, then the value is read and written back
+            Tok::Assign
+            | Tok::PlusAssign
+            | Tok::MinusAssign
+            | Tok::StarAssign
+            | Tok::SlashAssign
+            | Tok::CaretAssign
+            | Tok::ModuloAssign => {
+                if !assignable {
+                    return Err(LanguloErr::semantic_at(
+                        "Only a variable or an index can be assigned to",
+                        &self.lexer.span(),
+                    ));
+                }
+                self.builder.start_node_at(checkpoint, Expr::Assign.into());
+                self.token(tok, content);
+                self.parse_expr(precedence - 1)?;
+                self.builder.finish_node();
+            }
             Tok::Plus
             | Tok::Minus
+            | Tok::Else
             | Tok::Star
+            | Tok::Slash
+            | Tok::Modulo
+            | Tok::BitAnd
+            | Tok::BitOr
+            | Tok::BitXor
+            | Tok::ShiftLeft
+            | Tok::ShiftRight
             | Tok::And
             | Tok::Or
-            => self.new_binary_node(content, checkpoint, precedence)?,
-
-            Tok::Not => {
-                self.new_unary_node(content, checkpoint, precedence)?;
+            | Tok::Xor
+            | Tok::GreaterThan
+            | Tok::LessThan
+            | Tok::GreaterThanEq
+            | Tok::LessThanEq
+            | Tok::Equals
+            | Tok::NotEquals
+            => self.new_binary_node(tok, content, checkpoint, precedence)?,
+            // right associative: the right operand may continue with another `^`
+            Tok::Caret => self.new_binary_node(tok, content, checkpoint, precedence - 1)?,
+            // ranges are values of their own, that can be stored, indexed with and iterated on
+            Tok::Range => {
+                self.builder.start_node_at(checkpoint, Expr::Range.into());
+                self.token(tok, content);
+                self.parse_expr(precedence)?;
+                self.builder.finish_node();
+                if self.peek_tok()? == Some(Tok::Range) {
+                    next!(self);
+                    return Err(LanguloErr::semantic_at("Ranges can't be chained", &self.lexer.span()));
+                }
+            }
+            // `x as int` converts or fails at runtime, while `x as int?` is `no` when it can't convert
+            Tok::As => {
+                self.builder.start_node_at(checkpoint, Expr::Cast.into());
+                self.token(tok, content);
+                self.parse_type()?;
+                self.builder.finish_node();
+            }
+            Tok::At => self.parse_call(content, checkpoint)?,
+            Tok::LBracket => self.parse_index(content, checkpoint)?,
+            Tok::Dot => {
+                self.builder.start_node_at(checkpoint, Expr::FieldAccess.into());
+                self.token(tok, content);
+                self.consume(Tok::Identifier)?;
+                self.builder.finish_node();
+            }
+            Tok::Question | Tok::Bang => {
+                let expr = if tok == Tok::Question { Expr::Optional } else { Expr::Unwrap };
+                self.builder.start_node_at(checkpoint, expr.into());
+                self.token(tok, content);
+                self.builder.finish_node();
             }
             _ => return Err(LanguloErr::semantic(
-                &*format!("Expected an infix or postfix operator, but found {}", content)
+                &format!("Expected an infix or postfix operator, but found {}", content)
             ))
         }
         Ok(())
     }
 
+    /// parses an application `principal @name(contour, args)`. the principal is already
+    /// in the tree at `checkpoint`, and the parenthesized contour arguments are optional.
+    /// a lambda can be applied in place of a named function: `principal @|it + 1|`.
+    /// applications chain left to right, so `data @filter(|..|) @map(|..|)` maps the filtered data.
+    /// the pipes delimit a `|it ..|` lambda completely, so an `@` inside them never reaches outside.
+    fn parse_call(&mut self, content: &str, checkpoint: Checkpoint) -> Result<(), LanguloErr> {
+        self.builder.start_node_at(checkpoint, Expr::Call.into());
+        self.token(Tok::At, content);
+        self.skip_trivia()?;
+        let (tok, name) = next!(self);
+        match tok {
+            Tok::Identifier => self.new_leaf_node(Expr::Identifier, tok, name)?,
+            Tok::Pipe => self.parse_lambda(name, true)?,
+            _ => return Err(LanguloErr::semantic(
+                &format!("Expected a function name after @, but found {}", name)
+            )),
+        }
+
+        if self.peek_tok()? == Some(Tok::LParen) {
+            self.parse_args()?;
+        }
+        self.builder.finish_node();
+        Ok(())
+    }
+
+    /// parses parenthesized contour arguments: positional ones first, then named ones
+    fn parse_args(&mut self) -> Result<(), LanguloErr> {
+        self.consume(Tok::LParen)?;
+        self.nesting += 1;
+        let mut names = Vec::new();
+        self.parse_comma_separated(&[Tok::RParen], |parser| {
+            match parser.parse_arg()? {
+                (Some(name), span) if names.contains(&name) => Err(LanguloErr::semantic_at(
+                    &format!("Argument {} is passed more than once", name), &span,
+                )),
+                (Some(name), _) => {
+                    names.push(name);
+                    Ok(())
+                }
+                (None, span) if !names.is_empty() => Err(LanguloErr::semantic_at(
+                    "Positional arguments must come before named ones", &span,
+                )),
+                (None, _) => Ok(()),
+            }
+        })?;
+        self.nesting -= 1;
+        self.consume(Tok::RParen)
+    }
+
+    /// parses a contour argument, which is either positional (`3`) or named (`width: 3`).
+    /// returns the name of a named argument and where the name is, or else where the whole argument is.
+    fn parse_arg(&mut self) -> Result<(Option<&'a str>, Span), LanguloErr> {
+        let starts_with_identifier = self.peek_tok()? == Some(Tok::Identifier);
+        let start = self.lexer.peek_span()?.start;
+        self.flush_trivia();
+        let checkpoint = self.builder.checkpoint();
+        self.parse_prefix()?;
+
+        let name = self.lexer.last();
+        let span = self.lexer.span();
+        if starts_with_identifier && self.peek_tok()? == Some(Tok::Colon) {
+            self.consume(Tok::Colon)?;
+            self.parse_expr(0)?;
+            self.builder.start_node_at(checkpoint, Expr::NamedArg.into());
+            self.builder.finish_node();
+            return Ok((Some(name), span));
+        }
+        self.parse_infix(checkpoint, 0, starts_with_identifier)?;
+        Ok((None, start..self.lexer.span().end))
+    }
+
+    /// parses a lookup `table[key]`. several keys can be given as fallbacks,
+    /// `table[k1, k2]` being the value of the first key that is present.
+    fn parse_index(&mut self, content: &str, checkpoint: Checkpoint) -> Result<(), LanguloErr> {
+        self.builder.start_node_at(checkpoint, Expr::Index.into());
+        self.token(Tok::LBracket, content);
+        if self.peek_tok()? == Some(Tok::RBracket) {
+            next!(self);
+            return Err(LanguloErr::semantic_at("Expected at least one key to look up", &self.lexer.span()));
+        }
+        self.nesting += 1;
+        self.parse_comma_separated(&[Tok::RBracket], |parser| parser.parse_key())?;
+        self.nesting -= 1;
+        self.consume(Tok::RBracket)?;
+        self.builder.finish_node();
+        Ok(())
+    }
+
     fn parse_prefix(&mut self) -> Result<(), LanguloErr> {
         self.skip_trivia()?;
         let (tok, content) = next!(self);
+        let negated = std::mem::take(&mut self.negated);
+
+        match tok {
+            Tok::Int => {
+                // ints are stored in 32 bits at runtime, so anything larger would silently wrap
+                let span = self.lexer.span();
+                let magnitude = content.parse::<i64>().unwrap_or(i64::MAX);
+                self.new_leaf_node(Expr::Int, tok, content)?;
+                // the magnitude of the smallest int only fits once negated, as in `-2147483648`,
+                // but not in `-2147483648 ^ 2`, where the literal is an operand of `^`
+                let negated = negated
+                    && self.peek_tok()?.is_none_or(|tok| tok.precedence() <= PREFIX_PRECEDENCE);
+                if magnitude > i32::MAX as i64 + i64::from(negated) {
+                    return Err(LanguloErr::semantic_at(
+                        &format!("Int literal {} doesn't fit in 32 bits", content), &span,
+                    ));
+                }
+                Ok(())
+            }
+            // rust parses floats the same way whatever the locale, so the separator is always `.`
+            Tok::Float => {
+                if !content.parse::<f64>().is_ok_and(f64::is_finite) {
+                    return Err(LanguloErr::semantic_at(
+                        &format!("Float literal {} is too large", content),
+                        &self.lexer.span(),
+                    ));
+                }
+                self.new_leaf_node(Expr::Float, tok, content)
+            }
+            Tok::Identifier => self.new_leaf_node(Expr::Identifier, tok, content),
+            Tok::String | Tok::RawString => self.new_leaf_node(Expr::Str, tok, content),
+            Tok::No => self.new_leaf_node(Expr::No, tok, content),
+            Tok::Bool => self.new_leaf_node(Expr::Bool, tok, content),
+            Tok::Char => self.new_leaf_node(Expr::Char, tok, content),
+            Tok::At => {
+                let checkpoint = self.builder.checkpoint();
+                self.parse_call(content, checkpoint)
+            }
+            Tok::Pipe => self.parse_lambda(content, false),
+            // literals have no sign of their own: `-1` negates `1`, just as `-x` negates `x`
+            Tok::Not | Tok::BitNot | Tok::Minus | Tok::TypeOf => {
+                self.start_node(Expr::Unary);
+                self.token(tok, content);
+                self.negated = tok == Tok::Minus;
+                self.parse_expr(PREFIX_PRECEDENCE)?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            // `$` prints everything to its right and evaluates to it
+            Tok::Dollar => {
+                self.start_node(Expr::Print);
+                self.token(tok, content);
+                self.parse_expr(0)?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::LParen => {
+                self.start_node(Expr::Group);
+                self.token(tok, content);
+                self.nesting += 1;
+                self.parse_expr(0)?;
+                self.nesting -= 1;
+                self.consume(Tok::RParen)?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::LBrace => {
+                self.start_node(Expr::Scope);
+                self.token(tok, content);
+                self.parse_scope_body()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::Fn => {
+                self.start_node(Expr::FnDecl);
+                self.token(tok, content);
+                self.parse_fn_decl()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::Var => {
+                self.start_node(Expr::VarDecl);
+                self.token(tok, content);
+                self.skip_trivia()?;
+                let (tok, name) = next!(self);
+                if tok != Tok::Identifier {
+                    return Err(LanguloErr::semantic(
+                        &format!("Expected the name of the variable, but found {}", name)
+                    ));
+                }
+                self.new_leaf_node(Expr::Identifier, tok, name)?;
+                self.consume(Tok::Assign)?;
+                self.parse_expr(0)?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::Struct => {
+                self.start_node(Expr::StructDecl);
+                self.token(tok, content);
+                self.parse_struct_decl()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            // `new Point(x: 1, y: 2)`, with the same arguments as a call
+            Tok::New => {
+                self.start_node(Expr::New);
+                self.token(tok, content);
+                self.consume(Tok::Identifier)?;
+                self.parse_args()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::While => {
+                self.start_node(Expr::While);
+                self.token(tok, content);
+                self.parse_expr(0)?;
+                self.parse_block()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::Match => {
+                self.start_node(Expr::Match);
+                self.token(tok, content);
+                self.parse_expr(0)?;
+                self.parse_match_arms()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            // `iter source { body }`, with the body seeing each element as `it`
+            Tok::Iter => {
+                self.start_node(Expr::Iterate);
+                self.token(tok, content);
+                self.parse_expr(0)?;
+                self.parse_block()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::Test => {
+                self.start_node(Expr::TestBlock);
+                self.token(tok, content);
+                self.skip_trivia()?;
+                let (tok, name) = next!(self);
+                if tok != Tok::String {
+                    return Err(LanguloErr::semantic(
+                        &format!("Expected the name of the test, but found {}", name)
+                    ));
+                }
+                self.new_leaf_node(Expr::Str, tok, name)?;
+                self.parse_block()?;
+                self.builder.finish_node();
+                Ok(())
+            }
+            Tok::LBracket => {
+                let checkpoint = self.builder.checkpoint();
+                self.token(tok, content);
+                self.nesting += 1;
+                self.parse_table(checkpoint)?;
+                self.nesting -= 1;
+                Ok(())
+            }
+            _ => Err(LanguloErr::semantic(
+                &format!("Expected an expression, but found {}", content)
+            ))
+        }
+    }
+
+    /// parses a lambda `|it * 2|` after its opening pipe. its body refers to the argument as `it`.
+    /// parameters can be named instead, with optional types: `|x int, y| x + y`.
+    /// then the body follows the pipes, and extends as far as an expression can. if the lambda is
+    /// `applied` with `@`, its body ends before the next `@`: `3 @|x int| x * 2 @g` applies `g` last.
+    fn parse_lambda(&mut self, content: &str, applied: bool) -> Result<(), LanguloErr> {
+        self.start_node(Expr::Lambda);
+        self.token(Tok::Pipe, content);
+        self.nesting += 1;
+        let mut body_is_name = false;
+        if self.peek_tok()? != Some(Tok::Identifier) {
+            self.parse_expr(0)?;
+        } else {
+            // `|x` starts either the parameters or the body: only a type or a comma after it tells
+            self.flush_trivia();
+            let checkpoint = self.builder.checkpoint();
+            let (tok, name) = next!(self);
+            self.token(tok, name);
+            let next = self.peek_tok()?;
+            if next.is_some_and(|tok| tok == Tok::Comma || tok.starts_type()) {
+                self.builder.start_node_at(checkpoint, Expr::Param.into());
+                if next != Some(Tok::Comma) { self.parse_type()?; }
+                self.builder.finish_node();
+                if self.peek_tok()? == Some(Tok::Comma) {
+                    self.consume(Tok::Comma)?;
+                    self.parse_comma_separated(&[Tok::Pipe], Self::parse_lambda_param)?;
+                }
+                self.nesting -= 1;
+                self.consume(Tok::Pipe)?;
+                let outer_body = self.applied_body;
+                if applied { self.applied_body = Some(self.nesting); }
+                let body = self.parse_expr(0);
+                self.applied_body = outer_body;
+                body?;
+                self.builder.finish_node();
+                return Ok(());
+            }
+            self.builder.start_node_at(checkpoint, Expr::Identifier.into());
+            self.builder.finish_node();
+            body_is_name = self.peek_tok()? == Some(Tok::Pipe);
+            self.parse_infix(checkpoint, 0, true)?;
+        }
+        self.nesting -= 1;
+        self.consume(Tok::Pipe)?;
+        self.builder.finish_node();
+        // `|x| x + 1` is the lambda `|x|` followed by a stray `x + 1`: point to the form that was meant
+        if body_is_name && self.peek_tok()?.is_some_and(|tok| tok.starts_operand()) && !self.newline {
+            next!(self);
+            return Err(LanguloErr::semantic_at(
+                "A lambda with a single untyped parameter is written |x,| body", &self.lexer.span(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// parses a lambda parameter: a name, optionally followed by its type
+    fn parse_lambda_param(&mut self) -> Result<(), LanguloErr> {
+        self.skip_trivia()?;
+        self.start_node(Expr::Param);
+        self.consume(Tok::Identifier)?;
+        if self.peek_tok()?.is_some_and(|tok| tok.starts_type()) {
+            self.parse_type()?;
+        }
+        self.builder.finish_node();
+        Ok(())
+    }
+
+    /// parses what follows `fn`: `name(@int, other int) int { body }`.
+    /// the name is omitted for anonymous functions, and the return type for those returning nothing.
+    /// since this is an expression like any other, functions can be declared in any scope.
+    fn parse_fn_decl(&mut self) -> Result<(), LanguloErr> {
+        if self.peek_tok()? == Some(Tok::Identifier) {
+            self.consume(Tok::Identifier)?;
+        }
+        self.consume(Tok::LParen)?;
+        self.nesting += 1;
+        // callers can only omit trailing arguments, so defaults can't be followed by required params
+        let mut previous = ParamKind::Required;
+        self.parse_comma_separated(&[Tok::RParen], |parser| {
+            let (kind, span) = parser.parse_param()?;
+            match (previous, kind) {
+                (ParamKind::Variadic, _) => return Err(LanguloErr::semantic_at(
+                    "The variadic parameter must be the last one", &span,
+                )),
+                (ParamKind::Defaulted, ParamKind::Required) => return Err(LanguloErr::semantic_at(
+                    "Parameters with a default value must come after the others", &span,
+                )),
+                _ => {}
+            }
+            previous = kind;
+            Ok(())
+        })?;
+        self.nesting -= 1;
+        self.consume(Tok::RParen)?;
+        if self.peek_tok()? != Some(Tok::LBrace) {
+            self.parse_type()?;
+        }
+        self.parse_block()
+    }
+
+    /// parses what follows `struct`: `Point { x int, y int }`
+    fn parse_struct_decl(&mut self) -> Result<(), LanguloErr> {
+        self.consume(Tok::Identifier)?;
+        self.consume(Tok::LBrace)?;
+        self.nesting += 1;
+        let mut names = Vec::new();
+        self.parse_comma_separated(&[Tok::RBrace], |parser| {
+            parser.skip_trivia()?;
+            parser.start_node(Expr::Field);
+            parser.consume(Tok::Identifier)?;
+            let (name, span) = (parser.lexer.last(), parser.lexer.span());
+            if names.contains(&name) {
+                return Err(LanguloErr::semantic_at(
+                    &format!("Field {} is declared more than once", name), &span,
+                ));
+            }
+            names.push(name);
+            parser.parse_type()?;
+            parser.builder.finish_node();
+            Ok(())
+        })?;
+        self.nesting -= 1;
+        self.consume(Tok::RBrace)
+    }
+
+    /// parses the arms of a match, in the style of a table: `{ 1: "one", str: "a string", _: "other" }`.
+    /// an arm matches either a value or a type, and `_` matches anything, so it can only come last.
+    fn parse_match_arms(&mut self) -> Result<(), LanguloErr> {
+        self.consume(Tok::LBrace)?;
+        if self.peek_tok()? == Some(Tok::RBrace) {
+            next!(self);
+            return Err(LanguloErr::semantic_at("Expected at least one arm in match", &self.lexer.span()));
+        }
+        self.nesting += 1;
+        let mut after_default = false;
+        self.parse_comma_separated(&[Tok::RBrace], |parser| {
+            let next = parser.peek_tok()?;
+            if after_default {
+                next!(parser);
+                return Err(LanguloErr::semantic_at(
+                    "This arm can never match, since it comes after _", &parser.lexer.span(),
+                ));
+            }
+            parser.start_node(Expr::MatchArm);
+            match next {
+                // only a type keyword starts a type pattern: an identifier is a variable holding
+                // the value to compare with, and a parenthesis groups a value
+                Some(
+                    Tok::TypeInt | Tok::TypeFloat | Tok::TypeString | Tok::TypeBool | Tok::TypeChar | Tok::Fn
+                ) => parser.parse_type()?,
+                _ => parser.parse_key()?,
+            }
+            after_default = next == Some(Tok::Underscore);
+            parser.consume(Tok::Colon)?;
+            parser.parse_expr(0)?;
+            parser.builder.finish_node();
+            Ok(())
+        })?;
+        self.nesting -= 1;
+        self.consume(Tok::RBrace)
+    }
+
+    /// parses either the principal parameter (`@int`) or a contour parameter (`name int`).
+    /// a contour parameter can have a default value (`step int = 1`),
+    /// or collect all the remaining arguments (`rest... int`).
+    /// the kind of parameter is returned along with the span of its name, or of its `@`.
+    fn parse_param(&mut self) -> Result<(ParamKind, Span), LanguloErr> {
+        self.skip_trivia()?;
+        self.start_node(Expr::Param);
+        let principal = self.peek_tok()? == Some(Tok::At);
+        self.consume(if principal { Tok::At } else { Tok::Identifier })?;
+        let span = self.lexer.span();
+        let mut kind = ParamKind::Required;
+        if !principal && self.peek_tok()? == Some(Tok::Ellipsis) {
+            self.consume(Tok::Ellipsis)?;
+            kind = ParamKind::Variadic;
+        }
+        self.parse_type()?;
+        if self.peek_tok()? == Some(Tok::Assign) {
+            if principal || kind == ParamKind::Variadic {
+                return Err(LanguloErr::semantic(
+                    "Only a contour parameter that takes a single argument can have a default value"
+                ));
+            }
+            self.consume(Tok::Assign)?;
+            self.parse_expr(0)?;
+            kind = ParamKind::Defaulted;
+        }
+        self.builder.finish_node();
+        Ok((kind, span))
+    }
+
+    /// parses the type of a function's parameter in a function type: `@int` or `int`
+    fn parse_param_type(&mut self) -> Result<(), LanguloErr> {
+        self.skip_trivia()?;
+        self.start_node(Expr::Param);
+        if self.peek_tok()? == Some(Tok::At) {
+            self.consume(Tok::At)?;
+        }
+        self.parse_type()?;
+        self.builder.finish_node();
+        Ok(())
+    }
+
+    /// parses a type annotation: a primitive, a named type or a function type like `fn(@int, str -> int)`,
+    /// optionally followed by `?`s. the return type is omitted for functions returning nothing.
+    /// a type can be grouped in parens, so that the `?`s apply to all of it: `(int?)?`
+    fn parse_type(&mut self) -> Result<(), LanguloErr> {
+        self.skip_trivia()?;
+        self.start_node(Expr::Type);
+        let (tok, content) = next!(self);
+        match tok {
+            Tok::TypeInt
+            | Tok::TypeFloat
+            | Tok::TypeString
+            | Tok::TypeBool
+            | Tok::TypeChar
+            | Tok::Identifier
+            => self.token(tok, content),
+            Tok::LParen => {
+                self.token(tok, content);
+                self.nesting += 1;
+                self.parse_type()?;
+                self.nesting -= 1;
+                self.consume(Tok::RParen)?;
+            }
+            Tok::Fn => {
+                self.token(tok, content);
+                self.consume(Tok::LParen)?;
+                self.nesting += 1;
+                self.parse_comma_separated(&[Tok::Arrow, Tok::RParen], Self::parse_param_type)?;
+                if self.peek_tok()? == Some(Tok::Arrow) {
+                    self.consume(Tok::Arrow)?;
+                    self.parse_type()?;
+                }
+                self.nesting -= 1;
+                self.consume(Tok::RParen)?;
+            }
+            _ => return Err(LanguloErr::semantic(
+                &format!("Expected a type, but found {}", content)
+            ))
+        }
+        while self.peek_tok()? == Some(Tok::Question) {
+            self.consume(Tok::Question)?;
+        }
+        self.builder.finish_node();
+        Ok(())
+    }
+
+    /// parses a scope that is required by the syntax, such as a function's body
+    fn parse_block(&mut self) -> Result<(), LanguloErr> {
+        self.skip_trivia()?;
+        self.start_node(Expr::Scope);
+        self.consume(Tok::LBrace)?;
+        self.parse_scope_body()?;
+        self.builder.finish_node();
+        Ok(())
+    }
+
+    /// parses the statements following a `{`, and the closing `}`.
+    /// newlines are significant again inside a scope, even within brackets,
+    /// and `@` chains within it even if the scope is the body of an applied lambda.
+    fn parse_scope_body(&mut self) -> Result<(), LanguloErr> {
+        let nesting = std::mem::take(&mut self.nesting);
+        let applied_body = self.applied_body.take();
+        self.parse_statements(Some(Tok::RBrace))?;
+        self.nesting = nesting;
+        self.applied_body = applied_body;
+        self.consume(Tok::RBrace)
+    }
+
+    /// parses what follows a `[`: either a table literal (`[k: v, k2: v2]`)
+    /// or a comprehension (`[k: v iter source]`). the two forms share their opening,
+    /// so the kind of the node is only decided once the closing bracket is reached.
+    fn parse_table(&mut self, checkpoint: Checkpoint) -> Result<(), LanguloErr> {
+        let mut kind = Expr::Table;
+        if self.peek_tok()? != Some(Tok::RBracket) {
+            self.parse_table_entry()?;
+            match self.peek_tok()? {
+                Some(Tok::Iter) => {
+                    kind = Expr::Comprehension;
+                    self.consume(Tok::Iter)?;
+                    self.parse_expr(0)?;
+                }
+                Some(Tok::Comma) => {
+                    self.consume(Tok::Comma)?;
+                    self.parse_comma_separated(&[Tok::RBracket], Self::parse_table_entry)?;
+                    if self.peek_tok()? == Some(Tok::Iter) {
+                        return Err(LanguloErr::semantic(
+                            "A comprehension must have exactly one entry before iter"
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.consume(Tok::RBracket)?;
+        self.builder.start_node_at(checkpoint, kind.into());
+        self.builder.finish_node();
+        Ok(())
+    }
+
+    fn parse_table_entry(&mut self) -> Result<(), LanguloErr> {
+        self.parse_key()?;
+        if self.peek_tok()? == Some(Tok::Colon) {
+            self.consume(Tok::Colon)?;
+            self.parse_expr(0)?;
+        }
+        Ok(())
+    }
 
-        if matches!(tok, Tok::Int) { self.new_leaf_node(Expr::Int, content) } else { Ok(()) }
+    /// a key is any expression, or `_` for the default entry that answers every missing key
+    fn parse_key(&mut self) -> Result<(), LanguloErr> {
+        if self.peek_tok()? == Some(Tok::Underscore) {
+            let (tok, content) = next!(self);
+            return self.new_leaf_node(Expr::DefaultKey, tok, content);
+        }
+        self.parse_expr(0)
+    }
+
+    /// parses `item, item, ...` up to, but excluding, any of the `closing` tokens.
+    /// every list in the language goes through here, so they all accept a trailing comma.
+    fn parse_comma_separated(
+        &mut self,
+        closing: &[Tok],
+        mut parse_item: impl FnMut(&mut Self) -> Result<(), LanguloErr>,
+    ) -> Result<(), LanguloErr> {
+        while !self.peek_tok()?.is_some_and(|tok| closing.contains(&tok)) {
+            parse_item(self)?;
+            if self.peek_tok()? != Some(Tok::Comma) { break; }
+            self.consume(Tok::Comma)?;
+        }
+        Ok(())
     }
 
     fn skip_trivia(&mut self) -> Result<(), LanguloErr> {
         while let Some((tok, content)) = self.lexer.peek()? {
             match tok {
-                Tok::Whitespace => {
-                    self.builder.token(Expr::Whitespace.into(), content);
-                    self.lexer.next()?;
-                }
-                Tok::Comment => {
-                    self.builder.token(Expr::Comment.into(), content);
+                Tok::Whitespace | Tok::Comment => {
+                    self.newline |= content.contains('\n');
+                    self.trivia.push((*tok, content));
                     self.lexer.next()?;
                 }
                 _ => break,
@@ -132,18 +899,2255 @@ impl<'a> Parser<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::syntax_tree::lang::LanguloSyntaxNode;
+    use expect_test::{expect, Expect};
 
-    fn expect_parser(input: &str) {
+    fn expect_parser(input: &str, expected: Expect) {
         let mut parser = Parser::new(input);
         parser.parse().expect("failed to parse");
-        let node = parser.builder.finish();
-        let syntax_node = LanguloSyntaxNode::new_root(node);
-        println!("{:#?}", syntax_node)
+        expected.assert_eq(&format!("{:#?}", parser.into_ast()));
+    }
+
+    fn expect_lines(input: &str, expected: Expect) {
+        let mut parser = Parser::new(input).infer_semicolons(true);
+        parser.parse().expect("failed to parse");
+        expected.assert_eq(&format!("{:#?}", parser.into_ast()));
+    }
+
+    fn expect_parser_err(input: &str) {
+        assert!(Parser::new(input).parse().is_err(), "expected {input} to fail parsing");
+    }
+
+    fn expect_parser_err_at(input: &str, span: Span) {
+        match Parser::new(input).parse() {
+            Ok(()) => panic!("expected {input} to fail parsing"),
+            Err(err) => assert_eq!(err.span(), span, "wrong span for the error in {input}"),
+        }
     }
 
     #[test]
     fn arithmetic() {
-        expect_parser("1+2*3")
+        expect_parser("1+2*3", expect![[r#"
+            Root@0..5
+              Binary@0..5
+                Int@0..1
+                  Int@0..1 "1"
+                Plus@1..2 "+"
+                Binary@2..5
+                  Int@2..3
+                    Int@2..3 "2"
+                  Star@3..4 "*"
+                  Int@4..5
+                    Int@4..5 "3"
+        "#]])
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn division_and_modulo() {
+        // both bind like `*` and associate to the left
+        expect_parser("7 / 2 % 3 * 4", expect![[r#"
+            Root@0..13
+              Binary@0..13
+                Binary@0..9
+                  Binary@0..5
+                    Int@0..1
+                      Int@0..1 "7"
+                    Whitespace@1..2 " "
+                    Slash@2..3 "/"
+                    Whitespace@3..4 " "
+                    Int@4..5
+                      Int@4..5 "2"
+                  Whitespace@5..6 " "
+                  Modulo@6..7 "%"
+                  Whitespace@7..8 " "
+                  Int@8..9
+                    Int@8..9 "3"
+                Whitespace@9..10 " "
+                Star@10..11 "*"
+                Whitespace@11..12 " "
+                Int@12..13
+                  Int@12..13 "4"
+        "#]]);
+    }
+
+    #[test]
+    fn bitwise() {
+        // shifts bind tighter than band, then bxor, then bor, all looser than arithmetic
+        expect_parser("bnot a bor b bxor c band d shl 1 + 2", expect![[r#"
+            Root@0..36
+              Binary@0..36
+                Unary@0..6
+                  BitNot@0..4 "bnot"
+                  Whitespace@4..5 " "
+                  Identifier@5..6
+                    Identifier@5..6 "a"
+                Whitespace@6..7 " "
+                BitOr@7..10 "bor"
+                Whitespace@10..11 " "
+                Binary@11..36
+                  Identifier@11..12
+                    Identifier@11..12 "b"
+                  Whitespace@12..13 " "
+                  BitXor@13..17 "bxor"
+                  Whitespace@17..18 " "
+                  Binary@18..36
+                    Identifier@18..19
+                      Identifier@18..19 "c"
+                    Whitespace@19..20 " "
+                    BitAnd@20..24 "band"
+                    Whitespace@24..25 " "
+                    Binary@25..36
+                      Identifier@25..26
+                        Identifier@25..26 "d"
+                      Whitespace@26..27 " "
+                      ShiftLeft@27..30 "shl"
+                      Whitespace@30..31 " "
+                      Binary@31..36
+                        Int@31..32
+                          Int@31..32 "1"
+                        Whitespace@32..33 " "
+                        Plus@33..34 "+"
+                        Whitespace@34..35 " "
+                        Int@35..36
+                          Int@35..36 "2"
+        "#]]);
+    }
+
+    #[test]
+    fn tables() {
+        expect_parser("[1: 2, x: 3]", expect![[r#"
+            Root@0..12
+              Table@0..12
+                LBracket@0..1 "["
+                Int@1..2
+                  Int@1..2 "1"
+                Colon@2..3 ":"
+                Whitespace@3..4 " "
+                Int@4..5
+                  Int@4..5 "2"
+                Comma@5..6 ","
+                Whitespace@6..7 " "
+                Identifier@7..8
+                  Identifier@7..8 "x"
+                Colon@8..9 ":"
+                Whitespace@9..10 " "
+                Int@10..11
+                  Int@10..11 "3"
+                RBracket@11..12 "]"
+        "#]])
+    }
+
+    #[test]
+    fn comprehensions() {
+        expect_parser("[x: x*x iter 1..10]", expect![[r#"
+            Root@0..19
+              Comprehension@0..19
+                LBracket@0..1 "["
+                Identifier@1..2
+                  Identifier@1..2 "x"
+                Colon@2..3 ":"
+                Whitespace@3..4 " "
+                Binary@4..7
+                  Identifier@4..5
+                    Identifier@4..5 "x"
+                  Star@5..6 "*"
+                  Identifier@6..7
+                    Identifier@6..7 "x"
+                Whitespace@7..8 " "
+                Iter@8..12 "iter"
+                Whitespace@12..13 " "
+                Range@13..18
+                  Int@13..14
+                    Int@13..14 "1"
+                  Range@14..16 ".."
+                  Int@16..18
+                    Int@16..18 "10"
+                RBracket@18..19 "]"
+        "#]]);
+        expect_parser("[x iter 1..10]", expect![[r#"
+            Root@0..14
+              Comprehension@0..14
+                LBracket@0..1 "["
+                Identifier@1..2
+                  Identifier@1..2 "x"
+                Whitespace@2..3 " "
+                Iter@3..7 "iter"
+                Whitespace@7..8 " "
+                Range@8..13
+                  Int@8..9
+                    Int@8..9 "1"
+                  Range@9..11 ".."
+                  Int@11..13
+                    Int@11..13 "10"
+                RBracket@13..14 "]"
+        "#]]);
+        expect_parser_err("[x: 1, y: 2 iter 1..10]");
+        expect_parser_err("[x: x iter 1..10");
+    }
+
+    #[test]
+    fn calls() {
+        expect_parser("opt @map(f)", expect![[r#"
+            Root@0..11
+              Call@0..11
+                Identifier@0..3
+                  Identifier@0..3 "opt"
+                Whitespace@3..4 " "
+                At@4..5 "@"
+                Identifier@5..8
+                  Identifier@5..8 "map"
+                LParen@8..9 "("
+                Identifier@9..10
+                  Identifier@9..10 "f"
+                RParen@10..11 ")"
+        "#]]);
+        expect_parser("x @flatten + 1", expect![[r#"
+            Root@0..14
+              Binary@0..14
+                Call@0..10
+                  Identifier@0..1
+                    Identifier@0..1 "x"
+                  Whitespace@1..2 " "
+                  At@2..3 "@"
+                  Identifier@3..10
+                    Identifier@3..10 "flatten"
+                Whitespace@10..11 " "
+                Plus@11..12 "+"
+                Whitespace@12..13 " "
+                Int@13..14
+                  Int@13..14 "1"
+        "#]]);
+        expect_parser_err("x @(f)");
+    }
+
+    #[test]
+    fn chained_calls() {
+        expect_parser("3@double@add(2@square)@clamp(lo: 0)", expect![[r#"
+            Root@0..35
+              Call@0..35
+                Call@0..22
+                  Call@0..8
+                    Int@0..1
+                      Int@0..1 "3"
+                    At@1..2 "@"
+                    Identifier@2..8
+                      Identifier@2..8 "double"
+                  At@8..9 "@"
+                  Identifier@9..12
+                    Identifier@9..12 "add"
+                  LParen@12..13 "("
+                  Call@13..21
+                    Int@13..14
+                      Int@13..14 "2"
+                    At@14..15 "@"
+                    Identifier@15..21
+                      Identifier@15..21 "square"
+                  RParen@21..22 ")"
+                At@22..23 "@"
+                Identifier@23..28
+                  Identifier@23..28 "clamp"
+                LParen@28..29 "("
+                NamedArg@29..34
+                  Identifier@29..31
+                    Identifier@29..31 "lo"
+                  Colon@31..32 ":"
+                  Whitespace@32..33 " "
+                  Int@33..34
+                    Int@33..34 "0"
+                RParen@34..35 ")"
+        "#]]);
+        expect_parser("@f @g - 1", expect![[r#"
+            Root@0..9
+              Binary@0..9
+                Call@0..5
+                  Call@0..2
+                    At@0..1 "@"
+                    Identifier@1..2
+                      Identifier@1..2 "f"
+                  Whitespace@2..3 " "
+                  At@3..4 "@"
+                  Identifier@4..5
+                    Identifier@4..5 "g"
+                Whitespace@5..6 " "
+                Minus@6..7 "-"
+                Whitespace@7..8 " "
+                Int@8..9
+                  Int@8..9 "1"
+        "#]]);
+        expect_parser_err("3@double@");
+        expect_parser_err("3@@double");
+    }
+
+    #[test]
+    fn trailing_commas() {
+        expect_parser("[1: 2, 3: 4,]", expect![[r#"
+            Root@0..13
+              Table@0..13
+                LBracket@0..1 "["
+                Int@1..2
+                  Int@1..2 "1"
+                Colon@2..3 ":"
+                Whitespace@3..4 " "
+                Int@4..5
+                  Int@4..5 "2"
+                Comma@5..6 ","
+                Whitespace@6..7 " "
+                Int@7..8
+                  Int@7..8 "3"
+                Colon@8..9 ":"
+                Whitespace@9..10 " "
+                Int@10..11
+                  Int@10..11 "4"
+                Comma@11..12 ","
+                RBracket@12..13 "]"
+        "#]]);
+        expect_parser("[1,]", expect![[r#"
+            Root@0..4
+              Table@0..4
+                LBracket@0..1 "["
+                Int@1..2
+                  Int@1..2 "1"
+                Comma@2..3 ","
+                RBracket@3..4 "]"
+        "#]]);
+        expect_parser("x @f(1, 2,)", expect![[r#"
+            Root@0..11
+              Call@0..11
+                Identifier@0..1
+                  Identifier@0..1 "x"
+                Whitespace@1..2 " "
+                At@2..3 "@"
+                Identifier@3..4
+                  Identifier@3..4 "f"
+                LParen@4..5 "("
+                Int@5..6
+                  Int@5..6 "1"
+                Comma@6..7 ","
+                Whitespace@7..8 " "
+                Int@8..9
+                  Int@8..9 "2"
+                Comma@9..10 ","
+                RParen@10..11 ")"
+        "#]]);
+        expect_parser_err("[,]");
+        expect_parser_err("[1,,]");
+        expect_parser_err("x @f(,)");
+        expect_parser_err("x @f(1,,)");
+    }
+
+    #[test]
+    fn statements() {
+        expect_parser("1; x;", expect![[r#"
+            Root@0..5
+              Int@0..1
+                Int@0..1 "1"
+              Semicolon@1..2 ";"
+              Whitespace@2..3 " "
+              Identifier@3..4
+                Identifier@3..4 "x"
+              Semicolon@4..5 ";"
+        "#]]);
+        expect_parser("", expect![[r#"
+            Root@0..0
+        "#]]);
+        expect_parser_err("1 2");
+        expect_parser_err("1\n2");
+    }
+
+    #[test]
+    fn inferred_semicolons() {
+        expect_lines("1 + 2\nx", expect![[r#"
+            Root@0..7
+              Binary@0..5
+                Int@0..1
+                  Int@0..1 "1"
+                Whitespace@1..2 " "
+                Plus@2..3 "+"
+                Whitespace@3..4 " "
+                Int@4..5
+                  Int@4..5 "2"
+              Whitespace@5..6 "\n"
+              Identifier@6..7
+                Identifier@6..7 "x"
+        "#]]);
+        // an expression can't end on a binary operator, so the next line continues it
+        expect_lines("1 +\n2", expect![[r#"
+            Root@0..5
+              Binary@0..5
+                Int@0..1
+                  Int@0..1 "1"
+                Whitespace@1..2 " "
+                Plus@2..3 "+"
+                Whitespace@3..4 "\n"
+                Int@4..5
+                  Int@4..5 "2"
+        "#]]);
+        // newlines inside brackets never end a statement
+        expect_lines("[1,\n2] @f(x\n+ 1)", expect![[r#"
+            Root@0..16
+              Call@0..16
+                Table@0..6
+                  LBracket@0..1 "["
+                  Int@1..2
+                    Int@1..2 "1"
+                  Comma@2..3 ","
+                  Whitespace@3..4 "\n"
+                  Int@4..5
+                    Int@4..5 "2"
+                  RBracket@5..6 "]"
+                Whitespace@6..7 " "
+                At@7..8 "@"
+                Identifier@8..9
+                  Identifier@8..9 "f"
+                LParen@9..10 "("
+                Binary@10..15
+                  Identifier@10..11
+                    Identifier@10..11 "x"
+                  Whitespace@11..12 "\n"
+                  Plus@12..13 "+"
+                  Whitespace@13..14 " "
+                  Int@14..15
+                    Int@14..15 "1"
+                RParen@15..16 ")"
+        "#]]);
+        // a line starting with `@` or `.` continues the pipeline of the previous one
+        expect_lines("xs\n  @map(|it|)\n  .len\ny", expect![[r#"
+            Root@0..24
+              FieldAccess@0..22
+                Call@0..15
+                  Identifier@0..2
+                    Identifier@0..2 "xs"
+                  Whitespace@2..5 "\n  "
+                  At@5..6 "@"
+                  Identifier@6..9
+                    Identifier@6..9 "map"
+                  LParen@9..10 "("
+                  Lambda@10..14
+                    Pipe@10..11 "|"
+                    Identifier@11..13
+                      Identifier@11..13 "it"
+                    Pipe@13..14 "|"
+                  RParen@14..15 ")"
+                Whitespace@15..18 "\n  "
+                Dot@18..19 "."
+                Identifier@19..22 "len"
+              Whitespace@22..23 "\n"
+              Identifier@23..24
+                Identifier@23..24 "y"
+        "#]]);
+        // the first line is a complete expression, which leaves `+ 2` on its own
+        assert!(Parser::new("1\n+ 2").infer_semicolons(true).parse().is_err());
+    }
+
+    #[test]
+    fn test_blocks() {
+        expect_parser(r#"test "adds" { @assert_eq(1 + 1, 2); } test "empty" {}"#, expect![[r#"
+            Root@0..53
+              TestBlock@0..37
+                Test@0..4 "test"
+                Whitespace@4..5 " "
+                Str@5..11
+                  String@5..11 "\"adds\""
+                Whitespace@11..12 " "
+                Scope@12..37
+                  LBrace@12..13 "{"
+                  Whitespace@13..14 " "
+                  Call@14..34
+                    At@14..15 "@"
+                    Identifier@15..24
+                      Identifier@15..24 "assert_eq"
+                    LParen@24..25 "("
+                    Binary@25..30
+                      Int@25..26
+                        Int@25..26 "1"
+                      Whitespace@26..27 " "
+                      Plus@27..28 "+"
+                      Whitespace@28..29 " "
+                      Int@29..30
+                        Int@29..30 "1"
+                    Comma@30..31 ","
+                    Whitespace@31..32 " "
+                    Int@32..33
+                      Int@32..33 "2"
+                    RParen@33..34 ")"
+                  Semicolon@34..35 ";"
+                  Whitespace@35..36 " "
+                  RBrace@36..37 "}"
+              Whitespace@37..38 " "
+              TestBlock@38..53
+                Test@38..42 "test"
+                Whitespace@42..43 " "
+                Str@43..50
+                  String@43..50 "\"empty\""
+                Whitespace@50..51 " "
+                Scope@51..53
+                  LBrace@51..52 "{"
+                  RBrace@52..53 "}"
+        "#]]);
+        expect_parser_err("test adds { 1 }");
+        expect_parser_err(r#"test "adds" 1"#);
+    }
+
+    #[test]
+    fn no_literal() {
+        expect_parser("no else {x}", expect![[r#"
+            Root@0..11
+              Binary@0..11
+                No@0..2
+                  No@0..2 "no"
+                Whitespace@2..3 " "
+                Else@3..7 "else"
+                Whitespace@7..8 " "
+                Scope@8..11
+                  LBrace@8..9 "{"
+                  Identifier@9..10
+                    Identifier@9..10 "x"
+                  RBrace@10..11 "}"
+        "#]]);
+        expect_parser("a + no else 1 + 2", expect![[r#"
+            Root@0..17
+              Binary@0..17
+                Binary@0..6
+                  Identifier@0..1
+                    Identifier@0..1 "a"
+                  Whitespace@1..2 " "
+                  Plus@2..3 "+"
+                  Whitespace@3..4 " "
+                  No@4..6
+                    No@4..6 "no"
+                Whitespace@6..7 " "
+                Else@7..11 "else"
+                Whitespace@11..12 " "
+                Binary@12..17
+                  Int@12..13
+                    Int@12..13 "1"
+                  Whitespace@13..14 " "
+                  Plus@14..15 "+"
+                  Whitespace@15..16 " "
+                  Int@16..17
+                    Int@16..17 "2"
+        "#]]);
+        expect_parser_err("no else");
+    }
+
+    #[test]
+    fn int_literal_range() {
+        expect_parser("2147483647; -2147483648", expect![[r#"
+            Root@0..23
+              Int@0..10
+                Int@0..10 "2147483647"
+              Semicolon@10..11 ";"
+              Whitespace@11..12 " "
+              Unary@12..23
+                Minus@12..13 "-"
+                Int@13..23
+                  Int@13..23 "2147483648"
+        "#]]);
+        expect_parser_err("2147483648");
+        expect_parser_err("-2147483649");
+        expect_parser_err("-2147483648 ^ 2");
+        expect_parser_err("-(2147483648)");
+        expect_parser_err("1 + 99999999999");
+    }
+
+    #[test]
+    fn float_literals() {
+        expect_parser("1.5 * -0.25", expect![[r#"
+            Root@0..11
+              Binary@0..11
+                Float@0..3
+                  Float@0..3 "1.5"
+                Whitespace@3..4 " "
+                Star@4..5 "*"
+                Whitespace@5..6 " "
+                Unary@6..11
+                  Minus@6..7 "-"
+                  Float@7..11
+                    Float@7..11 "0.25"
+        "#]]);
+        expect_parser_err(&format!("1{}.0", "0".repeat(400)));
+        expect_parser_err("1,5");
+    }
+
+    #[test]
+    fn bool_and_char_literals() {
+        expect_parser("while true { 'c' == 'd' or false }", expect![[r#"
+            Root@0..34
+              While@0..34
+                While@0..5 "while"
+                Whitespace@5..6 " "
+                Bool@6..10
+                  Bool@6..10 "true"
+                Whitespace@10..11 " "
+                Scope@11..34
+                  LBrace@11..12 "{"
+                  Whitespace@12..13 " "
+                  Binary@13..32
+                    Binary@13..23
+                      Char@13..16
+                        Char@13..16 "'c'"
+                      Whitespace@16..17 " "
+                      Equals@17..19 "=="
+                      Whitespace@19..20 " "
+                      Char@20..23
+                        Char@20..23 "'d'"
+                    Whitespace@23..24 " "
+                    Or@24..26 "or"
+                    Whitespace@26..27 " "
+                    Bool@27..32
+                      Bool@27..32 "false"
+                  Whitespace@32..33 " "
+                  RBrace@33..34 "}"
+        "#]]);
+    }
+
+    #[test]
+    fn trivia_placement() {
+        // trailing trivia belongs to the enclosing node, not to the expression before it
+        expect_parser("[1 + 2 // two\n]  ", expect![[r#"
+            Root@0..17
+              Table@0..15
+                LBracket@0..1 "["
+                Binary@1..6
+                  Int@1..2
+                    Int@1..2 "1"
+                  Whitespace@2..3 " "
+                  Plus@3..4 "+"
+                  Whitespace@4..5 " "
+                  Int@5..6
+                    Int@5..6 "2"
+                Whitespace@6..7 " "
+                Comment@7..13 "// two"
+                Whitespace@13..14 "\n"
+                RBracket@14..15 "]"
+              Whitespace@15..17 "  "
+        "#]]);
+    }
+
+    #[test]
+    fn lossless() {
+        for input in [
+            "1+2*3",
+            " [x: x*x iter 1..10] // squares\n",
+            "test \"adds\" {\n  @assert_eq(1 + 1, 2);\n}\n",
+            "opt @map(f) else { no } ;",
+        ] {
+            let mut parser = Parser::new(input);
+            parser.parse().expect("failed to parse");
+            assert_eq!(parser.into_ast().text().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn print() {
+        expect_parser("$x + 1; [1: $y]", expect![[r#"
+            Root@0..15
+              Print@0..6
+                Dollar@0..1 "$"
+                Binary@1..6
+                  Identifier@1..2
+                    Identifier@1..2 "x"
+                  Whitespace@2..3 " "
+                  Plus@3..4 "+"
+                  Whitespace@4..5 " "
+                  Int@5..6
+                    Int@5..6 "1"
+              Semicolon@6..7 ";"
+              Whitespace@7..8 " "
+              Table@8..15
+                LBracket@8..9 "["
+                Int@9..10
+                  Int@9..10 "1"
+                Colon@10..11 ":"
+                Whitespace@11..12 " "
+                Print@12..14
+                  Dollar@12..13 "$"
+                  Identifier@13..14
+                    Identifier@13..14 "y"
+                RBracket@14..15 "]"
+        "#]]);
+        expect_parser_err("$");
+    }
+
+    #[test]
+    fn index() {
+        expect_parser("tbl[a, b] + [1: 2][1]", expect![[r#"
+            Root@0..21
+              Binary@0..21
+                Index@0..9
+                  Identifier@0..3
+                    Identifier@0..3 "tbl"
+                  LBracket@3..4 "["
+                  Identifier@4..5
+                    Identifier@4..5 "a"
+                  Comma@5..6 ","
+                  Whitespace@6..7 " "
+                  Identifier@7..8
+                    Identifier@7..8 "b"
+                  RBracket@8..9 "]"
+                Whitespace@9..10 " "
+                Plus@10..11 "+"
+                Whitespace@11..12 " "
+                Index@12..21
+                  Table@12..18
+                    LBracket@12..13 "["
+                    Int@13..14
+                      Int@13..14 "1"
+                    Colon@14..15 ":"
+                    Whitespace@15..16 " "
+                    Int@16..17
+                      Int@16..17 "2"
+                    RBracket@17..18 "]"
+                  LBracket@18..19 "["
+                  Int@19..20
+                    Int@19..20 "1"
+                  RBracket@20..21 "]"
+        "#]]);
+        // slicing by a range of keys is a lookup whose key is a range
+        expect_parser("tbl[1..5]", expect![[r#"
+            Root@0..9
+              Index@0..9
+                Identifier@0..3
+                  Identifier@0..3 "tbl"
+                LBracket@3..4 "["
+                Range@4..8
+                  Int@4..5
+                    Int@4..5 "1"
+                  Range@5..7 ".."
+                  Int@7..8
+                    Int@7..8 "5"
+                RBracket@8..9 "]"
+        "#]]);
+        expect_parser_err("tbl[]");
+    }
+
+    #[test]
+    fn fn_declarations() {
+        expect_parser("fn outer(@int, other str?) int { fn helper() {}; other }", expect![[r#"
+            Root@0..56
+              FnDecl@0..56
+                Fn@0..2 "fn"
+                Whitespace@2..3 " "
+                Identifier@3..8 "outer"
+                LParen@8..9 "("
+                Param@9..13
+                  At@9..10 "@"
+                  Type@10..13
+                    TypeInt@10..13 "int"
+                Comma@13..14 ","
+                Whitespace@14..15 " "
+                Param@15..25
+                  Identifier@15..20 "other"
+                  Whitespace@20..21 " "
+                  Type@21..25
+                    TypeString@21..24 "str"
+                    Question@24..25 "?"
+                RParen@25..26 ")"
+                Whitespace@26..27 " "
+                Type@27..30
+                  TypeInt@27..30 "int"
+                Whitespace@30..31 " "
+                Scope@31..56
+                  LBrace@31..32 "{"
+                  Whitespace@32..33 " "
+                  FnDecl@33..47
+                    Fn@33..35 "fn"
+                    Whitespace@35..36 " "
+                    Identifier@36..42 "helper"
+                    LParen@42..43 "("
+                    RParen@43..44 ")"
+                    Whitespace@44..45 " "
+                    Scope@45..47
+                      LBrace@45..46 "{"
+                      RBrace@46..47 "}"
+                  Semicolon@47..48 ";"
+                  Whitespace@48..49 " "
+                  Identifier@49..54
+                    Identifier@49..54 "other"
+                  Whitespace@54..55 " "
+                  RBrace@55..56 "}"
+        "#]]);
+        expect_parser("fn(@int) {}", expect![[r#"
+            Root@0..11
+              FnDecl@0..11
+                Fn@0..2 "fn"
+                LParen@2..3 "("
+                Param@3..7
+                  At@3..4 "@"
+                  Type@4..7
+                    TypeInt@4..7 "int"
+                RParen@7..8 ")"
+                Whitespace@8..9 " "
+                Scope@9..11
+                  LBrace@9..10 "{"
+                  RBrace@10..11 "}"
+        "#]]);
+        expect_parser_err("fn f(x) {}");
+        expect_parser_err("fn f(@int) int");
+    }
+
+    #[test]
+    fn named_args() {
+        expect_parser("@draw(3, width: 3, height: w + 1)", expect![[r#"
+            Root@0..33
+              Call@0..33
+                At@0..1 "@"
+                Identifier@1..5
+                  Identifier@1..5 "draw"
+                LParen@5..6 "("
+                Int@6..7
+                  Int@6..7 "3"
+                Comma@7..8 ","
+                Whitespace@8..9 " "
+                NamedArg@9..17
+                  Identifier@9..14
+                    Identifier@9..14 "width"
+                  Colon@14..15 ":"
+                  Whitespace@15..16 " "
+                  Int@16..17
+                    Int@16..17 "3"
+                Comma@17..18 ","
+                Whitespace@18..19 " "
+                NamedArg@19..32
+                  Identifier@19..25
+                    Identifier@19..25 "height"
+                  Colon@25..26 ":"
+                  Whitespace@26..27 " "
+                  Binary@27..32
+                    Identifier@27..28
+                      Identifier@27..28 "w"
+                    Whitespace@28..29 " "
+                    Plus@29..30 "+"
+                    Whitespace@30..31 " "
+                    Int@31..32
+                      Int@31..32 "1"
+                RParen@32..33 ")"
+        "#]]);
+        expect_parser_err("@draw(width: 3, width: 4)");
+        expect_parser_err("@draw(width: 3, 4)");
+        expect_parser_err("@draw(a + b: 3)");
+        expect_parser_err_at("@draw(width: 3, a + b)", 16..21);
+    }
+
+    #[test]
+    fn lambdas() {
+        expect_parser("3 @|it * 2|; |it|", expect![[r#"
+            Root@0..17
+              Call@0..11
+                Int@0..1
+                  Int@0..1 "3"
+                Whitespace@1..2 " "
+                At@2..3 "@"
+                Lambda@3..11
+                  Pipe@3..4 "|"
+                  Binary@4..10
+                    Identifier@4..6
+                      Identifier@4..6 "it"
+                    Whitespace@6..7 " "
+                    Star@7..8 "*"
+                    Whitespace@8..9 " "
+                    Int@9..10
+                      Int@9..10 "2"
+                  Pipe@10..11 "|"
+              Semicolon@11..12 ";"
+              Whitespace@12..13 " "
+              Lambda@13..17
+                Pipe@13..14 "|"
+                Identifier@14..16
+                  Identifier@14..16 "it"
+                Pipe@16..17 "|"
+        "#]]);
+        expect_parser_err("|it");
+        expect_parser_err("||");
+    }
+
+    #[test]
+    fn pipelines() {
+        expect_parser("data @filter(|it @even|) @map(|it * 2|) + 1", expect![[r#"
+            Root@0..43
+              Binary@0..43
+                Call@0..39
+                  Call@0..24
+                    Identifier@0..4
+                      Identifier@0..4 "data"
+                    Whitespace@4..5 " "
+                    At@5..6 "@"
+                    Identifier@6..12
+                      Identifier@6..12 "filter"
+                    LParen@12..13 "("
+                    Lambda@13..23
+                      Pipe@13..14 "|"
+                      Call@14..22
+                        Identifier@14..16
+                          Identifier@14..16 "it"
+                        Whitespace@16..17 " "
+                        At@17..18 "@"
+                        Identifier@18..22
+                          Identifier@18..22 "even"
+                      Pipe@22..23 "|"
+                    RParen@23..24 ")"
+                  Whitespace@24..25 " "
+                  At@25..26 "@"
+                  Identifier@26..29
+                    Identifier@26..29 "map"
+                  LParen@29..30 "("
+                  Lambda@30..38
+                    Pipe@30..31 "|"
+                    Binary@31..37
+                      Identifier@31..33
+                        Identifier@31..33 "it"
+                      Whitespace@33..34 " "
+                      Star@34..35 "*"
+                      Whitespace@35..36 " "
+                      Int@36..37
+                        Int@36..37 "2"
+                    Pipe@37..38 "|"
+                  RParen@38..39 ")"
+                Whitespace@39..40 " "
+                Plus@40..41 "+"
+                Whitespace@41..42 " "
+                Int@42..43
+                  Int@42..43 "1"
+        "#]]);
+    }
+
+    #[test]
+    fn while_loops() {
+        expect_parser("while x + 1 { $x; }; 2", expect![[r#"
+            Root@0..22
+              While@0..19
+                While@0..5 "while"
+                Whitespace@5..6 " "
+                Binary@6..11
+                  Identifier@6..7
+                    Identifier@6..7 "x"
+                  Whitespace@7..8 " "
+                  Plus@8..9 "+"
+                  Whitespace@9..10 " "
+                  Int@10..11
+                    Int@10..11 "1"
+                Whitespace@11..12 " "
+                Scope@12..19
+                  LBrace@12..13 "{"
+                  Whitespace@13..14 " "
+                  Print@14..16
+                    Dollar@14..15 "$"
+                    Identifier@15..16
+                      Identifier@15..16 "x"
+                  Semicolon@16..17 ";"
+                  Whitespace@17..18 " "
+                  RBrace@18..19 "}"
+              Semicolon@19..20 ";"
+              Whitespace@20..21 " "
+              Int@21..22
+                Int@21..22 "2"
+        "#]]);
+        expect_parser_err("while x");
+        expect_parser_err("while { 1 }");
+    }
+
+    #[test]
+    fn iterate() {
+        expect_parser("iter 1..3 { $it }", expect![[r#"
+            Root@0..17
+              Iterate@0..17
+                Iter@0..4 "iter"
+                Whitespace@4..5 " "
+                Range@5..9
+                  Int@5..6
+                    Int@5..6 "1"
+                  Range@6..8 ".."
+                  Int@8..9
+                    Int@8..9 "3"
+                Whitespace@9..10 " "
+                Scope@10..17
+                  LBrace@10..11 "{"
+                  Whitespace@11..12 " "
+                  Print@12..15
+                    Dollar@12..13 "$"
+                    Identifier@13..15
+                      Identifier@13..15 "it"
+                  Whitespace@15..16 " "
+                  RBrace@16..17 "}"
+        "#]]);
+        expect_parser("iter [a: 1] { $it }", expect![[r#"
+            Root@0..19
+              Iterate@0..19
+                Iter@0..4 "iter"
+                Whitespace@4..5 " "
+                Table@5..11
+                  LBracket@5..6 "["
+                  Identifier@6..7
+                    Identifier@6..7 "a"
+                  Colon@7..8 ":"
+                  Whitespace@8..9 " "
+                  Int@9..10
+                    Int@9..10 "1"
+                  RBracket@10..11 "]"
+                Whitespace@11..12 " "
+                Scope@12..19
+                  LBrace@12..13 "{"
+                  Whitespace@13..14 " "
+                  Print@14..17
+                    Dollar@14..15 "$"
+                    Identifier@15..17
+                      Identifier@15..17 "it"
+                  Whitespace@17..18 " "
+                  RBrace@18..19 "}"
+        "#]]);
+        expect_parser_err("iter { $it }");
+        expect_parser_err("iter 1..3");
+    }
+
+    #[test]
+    fn comparisons() {
+        expect_parser("1 < 2", expect![[r#"
+            Root@0..5
+              Binary@0..5
+                Int@0..1
+                  Int@0..1 "1"
+                Whitespace@1..2 " "
+                LessThan@2..3 "<"
+                Whitespace@3..4 " "
+                Int@4..5
+                  Int@4..5 "2"
+        "#]]);
+        expect_parser("a + 1 >= b and c != d or e == f", expect![[r#"
+            Root@0..31
+              Binary@0..31
+                Binary@0..21
+                  Binary@0..10
+                    Binary@0..5
+                      Identifier@0..1
+                        Identifier@0..1 "a"
+                      Whitespace@1..2 " "
+                      Plus@2..3 "+"
+                      Whitespace@3..4 " "
+                      Int@4..5
+                        Int@4..5 "1"
+                    Whitespace@5..6 " "
+                    GreaterThanEq@6..8 ">="
+                    Whitespace@8..9 " "
+                    Identifier@9..10
+                      Identifier@9..10 "b"
+                  Whitespace@10..11 " "
+                  And@11..14 "and"
+                  Whitespace@14..15 " "
+                  Binary@15..21
+                    Identifier@15..16
+                      Identifier@15..16 "c"
+                    Whitespace@16..17 " "
+                    NotEquals@17..19 "!="
+                    Whitespace@19..20 " "
+                    Identifier@20..21
+                      Identifier@20..21 "d"
+                Whitespace@21..22 " "
+                Or@22..24 "or"
+                Whitespace@24..25 " "
+                Binary@25..31
+                  Identifier@25..26
+                    Identifier@25..26 "e"
+                  Whitespace@26..27 " "
+                  Equals@27..29 "=="
+                  Whitespace@29..30 " "
+                  Identifier@30..31
+                    Identifier@30..31 "f"
+        "#]]);
+        expect_parser("x <= 1..3 else no", expect![[r#"
+            Root@0..17
+              Binary@0..17
+                Binary@0..9
+                  Identifier@0..1
+                    Identifier@0..1 "x"
+                  Whitespace@1..2 " "
+                  LessThanEq@2..4 "<="
+                  Whitespace@4..5 " "
+                  Range@5..9
+                    Int@5..6
+                      Int@5..6 "1"
+                    Range@6..8 ".."
+                    Int@8..9
+                      Int@8..9 "3"
+                Whitespace@9..10 " "
+                Else@10..14 "else"
+                Whitespace@14..15 " "
+                No@15..17
+                  No@15..17 "no"
+        "#]]);
+        expect_parser_err("1 <");
+    }
+
+    #[test]
+    fn power() {
+        expect_parser("2 ^ 3 ^ 2", expect![[r#"
+            Root@0..9
+              Binary@0..9
+                Int@0..1
+                  Int@0..1 "2"
+                Whitespace@1..2 " "
+                Caret@2..3 "^"
+                Whitespace@3..4 " "
+                Binary@4..9
+                  Int@4..5
+                    Int@4..5 "3"
+                  Whitespace@5..6 " "
+                  Caret@6..7 "^"
+                  Whitespace@7..8 " "
+                  Int@8..9
+                    Int@8..9 "2"
+        "#]]);
+        expect_parser("2 * x ^ 2", expect![[r#"
+            Root@0..9
+              Binary@0..9
+                Int@0..1
+                  Int@0..1 "2"
+                Whitespace@1..2 " "
+                Star@2..3 "*"
+                Whitespace@3..4 " "
+                Binary@4..9
+                  Identifier@4..5
+                    Identifier@4..5 "x"
+                  Whitespace@5..6 " "
+                  Caret@6..7 "^"
+                  Whitespace@7..8 " "
+                  Int@8..9
+                    Int@8..9 "2"
+        "#]]);
+        expect_parser_err("2 ^");
+    }
+
+    #[test]
+    fn assignments() {
+        expect_parser("var x = 3; x = x + 1", expect![[r#"
+            Root@0..20
+              VarDecl@0..9
+                Var@0..3 "var"
+                Whitespace@3..4 " "
+                Identifier@4..5
+                  Identifier@4..5 "x"
+                Whitespace@5..6 " "
+                Assign@6..7 "="
+                Whitespace@7..8 " "
+                Int@8..9
+                  Int@8..9 "3"
+              Semicolon@9..10 ";"
+              Whitespace@10..11 " "
+              Assign@11..20
+                Identifier@11..12
+                  Identifier@11..12 "x"
+                Whitespace@12..13 " "
+                Assign@13..14 "="
+                Whitespace@14..15 " "
+                Binary@15..20
+                  Identifier@15..16
+                    Identifier@15..16 "x"
+                  Whitespace@16..17 " "
+                  Plus@17..18 "+"
+                  Whitespace@18..19 " "
+                  Int@19..20
+                    Int@19..20 "1"
+        "#]]);
+        expect_parser("x = tbl[a] = 2 else 0", expect![[r#"
+            Root@0..21
+              Assign@0..21
+                Identifier@0..1
+                  Identifier@0..1 "x"
+                Whitespace@1..2 " "
+                Assign@2..3 "="
+                Whitespace@3..4 " "
+                Assign@4..21
+                  Index@4..10
+                    Identifier@4..7
+                      Identifier@4..7 "tbl"
+                    LBracket@7..8 "["
+                    Identifier@8..9
+                      Identifier@8..9 "a"
+                    RBracket@9..10 "]"
+                  Whitespace@10..11 " "
+                  Assign@11..12 "="
+                  Whitespace@12..13 " "
+                  Binary@13..21
+                    Int@13..14
+                      Int@13..14 "2"
+                    Whitespace@14..15 " "
+                    Else@15..19 "else"
+                    Whitespace@19..20 " "
+                    Int@20..21
+                      Int@20..21 "0"
+        "#]]);
+        expect_parser_err("var x");
+        expect_parser_err("var 3 = 3");
+        expect_parser_err("x + 1 = 2");
+        expect_parser_err("@f = 2");
+        expect_parser_err("x = ");
+    }
+
+    #[test]
+    fn xor() {
+        expect_parser("a xor b and c or d", expect![[r#"
+            Root@0..18
+              Binary@0..18
+                Binary@0..13
+                  Identifier@0..1
+                    Identifier@0..1 "a"
+                  Whitespace@1..2 " "
+                  Xor@2..5 "xor"
+                  Whitespace@5..6 " "
+                  Binary@6..13
+                    Identifier@6..7
+                      Identifier@6..7 "b"
+                    Whitespace@7..8 " "
+                    And@8..11 "and"
+                    Whitespace@11..12 " "
+                    Identifier@12..13
+                      Identifier@12..13 "c"
+                Whitespace@13..14 " "
+                Or@14..16 "or"
+                Whitespace@16..17 " "
+                Identifier@17..18
+                  Identifier@17..18 "d"
+        "#]]);
+    }
+
+    #[test]
+    fn negation() {
+        expect_parser("-x ^ 2 - -3", expect![[r#"
+            Root@0..11
+              Binary@0..11
+                Unary@0..6
+                  Minus@0..1 "-"
+                  Binary@1..6
+                    Identifier@1..2
+                      Identifier@1..2 "x"
+                    Whitespace@2..3 " "
+                    Caret@3..4 "^"
+                    Whitespace@4..5 " "
+                    Int@5..6
+                      Int@5..6 "2"
+                Whitespace@6..7 " "
+                Minus@7..8 "-"
+                Whitespace@8..9 " "
+                Unary@9..11
+                  Minus@9..10 "-"
+                  Int@10..11
+                    Int@10..11 "3"
+        "#]]);
+        expect_parser("- -x", expect![[r#"
+            Root@0..4
+              Unary@0..4
+                Minus@0..1 "-"
+                Whitespace@1..2 " "
+                Unary@2..4
+                  Minus@2..3 "-"
+                  Identifier@3..4
+                    Identifier@3..4 "x"
+        "#]]);
+        expect_parser("a-1; -2 ^ 2", expect![[r#"
+            Root@0..11
+              Binary@0..3
+                Identifier@0..1
+                  Identifier@0..1 "a"
+                Minus@1..2 "-"
+                Int@2..3
+                  Int@2..3 "1"
+              Semicolon@3..4 ";"
+              Whitespace@4..5 " "
+              Unary@5..11
+                Minus@5..6 "-"
+                Binary@6..11
+                  Int@6..7
+                    Int@6..7 "2"
+                  Whitespace@7..8 " "
+                  Caret@8..9 "^"
+                  Whitespace@9..10 " "
+                  Int@10..11
+                    Int@10..11 "2"
+        "#]]);
+        expect_parser_err("-");
+    }
+
+    #[test]
+    fn logical_not() {
+        expect_parser("a and not b", expect![[r#"
+            Root@0..11
+              Binary@0..11
+                Identifier@0..1
+                  Identifier@0..1 "a"
+                Whitespace@1..2 " "
+                And@2..5 "and"
+                Whitespace@5..6 " "
+                Unary@6..11
+                  Not@6..9 "not"
+                  Whitespace@9..10 " "
+                  Identifier@10..11
+                    Identifier@10..11 "b"
+        "#]]);
+        expect_parser("not not x or y", expect![[r#"
+            Root@0..14
+              Binary@0..14
+                Unary@0..9
+                  Not@0..3 "not"
+                  Whitespace@3..4 " "
+                  Unary@4..9
+                    Not@4..7 "not"
+                    Whitespace@7..8 " "
+                    Identifier@8..9
+                      Identifier@8..9 "x"
+                Whitespace@9..10 " "
+                Or@10..12 "or"
+                Whitespace@12..13 " "
+                Identifier@13..14
+                  Identifier@13..14 "y"
+        "#]]);
+        expect_parser_err("x not");
+    }
+
+    #[test]
+    fn compound_assignments() {
+        expect_parser("x += 1; t[k] ^= 2; t[a, b] %= x -= 1", expect![[r#"
+            Root@0..36
+              Assign@0..6
+                Identifier@0..1
+                  Identifier@0..1 "x"
+                Whitespace@1..2 " "
+                PlusAssign@2..4 "+="
+                Whitespace@4..5 " "
+                Int@5..6
+                  Int@5..6 "1"
+              Semicolon@6..7 ";"
+              Whitespace@7..8 " "
+              Assign@8..17
+                Index@8..12
+                  Identifier@8..9
+                    Identifier@8..9 "t"
+                  LBracket@9..10 "["
+                  Identifier@10..11
+                    Identifier@10..11 "k"
+                  RBracket@11..12 "]"
+                Whitespace@12..13 " "
+                CaretAssign@13..15 "^="
+                Whitespace@15..16 " "
+                Int@16..17
+                  Int@16..17 "2"
+              Semicolon@17..18 ";"
+              Whitespace@18..19 " "
+              Assign@19..36
+                Index@19..26
+                  Identifier@19..20
+                    Identifier@19..20 "t"
+                  LBracket@20..21 "["
+                  Identifier@21..22
+                    Identifier@21..22 "a"
+                  Comma@22..23 ","
+                  Whitespace@23..24 " "
+                  Identifier@24..25
+                    Identifier@24..25 "b"
+                  RBracket@25..26 "]"
+                Whitespace@26..27 " "
+                ModuloAssign@27..29 "%="
+                Whitespace@29..30 " "
+                Assign@30..36
+                  Identifier@30..31
+                    Identifier@30..31 "x"
+                  Whitespace@31..32 " "
+                  MinusAssign@32..34 "-="
+                  Whitespace@34..35 " "
+                  Int@35..36
+                    Int@35..36 "1"
+        "#]]);
+        expect_parser_err("x + 1 *= 2");
+        expect_parser_err("x /=");
+    }
+
+    #[test]
+    fn default_keys() {
+        expect_parser("var t = [a: 1, _: 0]; t[_] = 2", expect![[r#"
+            Root@0..30
+              VarDecl@0..20
+                Var@0..3 "var"
+                Whitespace@3..4 " "
+                Identifier@4..5
+                  Identifier@4..5 "t"
+                Whitespace@5..6 " "
+                Assign@6..7 "="
+                Whitespace@7..8 " "
+                Table@8..20
+                  LBracket@8..9 "["
+                  Identifier@9..10
+                    Identifier@9..10 "a"
+                  Colon@10..11 ":"
+                  Whitespace@11..12 " "
+                  Int@12..13
+                    Int@12..13 "1"
+                  Comma@13..14 ","
+                  Whitespace@14..15 " "
+                  DefaultKey@15..16
+                    Underscore@15..16 "_"
+                  Colon@16..17 ":"
+                  Whitespace@17..18 " "
+                  Int@18..19
+                    Int@18..19 "0"
+                  RBracket@19..20 "]"
+              Semicolon@20..21 ";"
+              Whitespace@21..22 " "
+              Assign@22..30
+                Index@22..26
+                  Identifier@22..23
+                    Identifier@22..23 "t"
+                  LBracket@23..24 "["
+                  DefaultKey@24..25
+                    Underscore@24..25 "_"
+                  RBracket@25..26 "]"
+                Whitespace@26..27 " "
+                Assign@27..28 "="
+                Whitespace@28..29 " "
+                Int@29..30
+                  Int@29..30 "2"
+        "#]]);
+        expect_parser_err("_");
+        expect_parser_err("t[_ + 1]");
+    }
+
+    #[test]
+    fn optionals() {
+        expect_parser("a + b?; (a + b)?", expect![[r#"
+            Root@0..16
+              Binary@0..6
+                Identifier@0..1
+                  Identifier@0..1 "a"
+                Whitespace@1..2 " "
+                Plus@2..3 "+"
+                Whitespace@3..4 " "
+                Optional@4..6
+                  Identifier@4..5
+                    Identifier@4..5 "b"
+                  Question@5..6 "?"
+              Semicolon@6..7 ";"
+              Whitespace@7..8 " "
+              Optional@8..16
+                Group@8..15
+                  LParen@8..9 "("
+                  Binary@9..14
+                    Identifier@9..10
+                      Identifier@9..10 "a"
+                    Whitespace@10..11 " "
+                    Plus@11..12 "+"
+                    Whitespace@12..13 " "
+                    Identifier@13..14
+                      Identifier@13..14 "b"
+                  RParen@14..15 ")"
+                Question@15..16 "?"
+        "#]]);
+        expect_parser("fn f(@(int?)?) int? { t[k]? }", expect![[r#"
+            Root@0..29
+              FnDecl@0..29
+                Fn@0..2 "fn"
+                Whitespace@2..3 " "
+                Identifier@3..4 "f"
+                LParen@4..5 "("
+                Param@5..13
+                  At@5..6 "@"
+                  Type@6..13
+                    LParen@6..7 "("
+                    Type@7..11
+                      TypeInt@7..10 "int"
+                      Question@10..11 "?"
+                    RParen@11..12 ")"
+                    Question@12..13 "?"
+                RParen@13..14 ")"
+                Whitespace@14..15 " "
+                Type@15..19
+                  TypeInt@15..18 "int"
+                  Question@18..19 "?"
+                Whitespace@19..20 " "
+                Scope@20..29
+                  LBrace@20..21 "{"
+                  Whitespace@21..22 " "
+                  Optional@22..27
+                    Index@22..26
+                      Identifier@22..23
+                        Identifier@22..23 "t"
+                      LBracket@23..24 "["
+                      Identifier@24..25
+                        Identifier@24..25 "k"
+                      RBracket@25..26 "]"
+                    Question@26..27 "?"
+                  Whitespace@27..28 " "
+                  RBrace@28..29 "}"
+        "#]]);
+        expect_parser_err("(a + b");
+        expect_parser_err("()");
+        expect_parser_err("fn f(@(int) { 1 }");
+    }
+
+    #[test]
+    fn unwrap() {
+        expect_parser("t[k]! + x?! != 1", expect![[r#"
+            Root@0..16
+              Binary@0..16
+                Binary@0..11
+                  Unwrap@0..5
+                    Index@0..4
+                      Identifier@0..1
+                        Identifier@0..1 "t"
+                      LBracket@1..2 "["
+                      Identifier@2..3
+                        Identifier@2..3 "k"
+                      RBracket@3..4 "]"
+                    Bang@4..5 "!"
+                  Whitespace@5..6 " "
+                  Plus@6..7 "+"
+                  Whitespace@7..8 " "
+                  Unwrap@8..11
+                    Optional@8..10
+                      Identifier@8..9
+                        Identifier@8..9 "x"
+                      Question@9..10 "?"
+                    Bang@10..11 "!"
+                Whitespace@11..12 " "
+                NotEquals@12..14 "!="
+                Whitespace@14..15 " "
+                Int@15..16
+                  Int@15..16 "1"
+        "#]]);
+        expect_parser_err("!x");
+    }
+
+    #[test]
+    fn structs() {
+        expect_parser("struct Point { x int, y int? }; var p = new Point(x: 1, y: 2); p.x = p.y!", expect![[r#"
+            Root@0..73
+              StructDecl@0..30
+                Struct@0..6 "struct"
+                Whitespace@6..7 " "
+                Identifier@7..12 "Point"
+                Whitespace@12..13 " "
+                LBrace@13..14 "{"
+                Whitespace@14..15 " "
+                Field@15..20
+                  Identifier@15..16 "x"
+                  Whitespace@16..17 " "
+                  Type@17..20
+                    TypeInt@17..20 "int"
+                Comma@20..21 ","
+                Whitespace@21..22 " "
+                Field@22..28
+                  Identifier@22..23 "y"
+                  Whitespace@23..24 " "
+                  Type@24..28
+                    TypeInt@24..27 "int"
+                    Question@27..28 "?"
+                Whitespace@28..29 " "
+                RBrace@29..30 "}"
+              Semicolon@30..31 ";"
+              Whitespace@31..32 " "
+              VarDecl@32..61
+                Var@32..35 "var"
+                Whitespace@35..36 " "
+                Identifier@36..37
+                  Identifier@36..37 "p"
+                Whitespace@37..38 " "
+                Assign@38..39 "="
+                Whitespace@39..40 " "
+                New@40..61
+                  New@40..43 "new"
+                  Whitespace@43..44 " "
+                  Identifier@44..49 "Point"
+                  LParen@49..50 "("
+                  NamedArg@50..54
+                    Identifier@50..51
+                      Identifier@50..51 "x"
+                    Colon@51..52 ":"
+                    Whitespace@52..53 " "
+                    Int@53..54
+                      Int@53..54 "1"
+                  Comma@54..55 ","
+                  Whitespace@55..56 " "
+                  NamedArg@56..60
+                    Identifier@56..57
+                      Identifier@56..57 "y"
+                    Colon@57..58 ":"
+                    Whitespace@58..59 " "
+                    Int@59..60
+                      Int@59..60 "2"
+                  RParen@60..61 ")"
+              Semicolon@61..62 ";"
+              Whitespace@62..63 " "
+              Assign@63..73
+                FieldAccess@63..66
+                  Identifier@63..64
+                    Identifier@63..64 "p"
+                  Dot@64..65 "."
+                  Identifier@65..66 "x"
+                Whitespace@66..67 " "
+                Assign@67..68 "="
+                Whitespace@68..69 " "
+                Unwrap@69..73
+                  FieldAccess@69..72
+                    Identifier@69..70
+                      Identifier@69..70 "p"
+                    Dot@70..71 "."
+                    Identifier@71..72 "y"
+                  Bang@72..73 "!"
+        "#]]);
+        expect_parser("new Point(1, 2).x", expect![[r#"
+            Root@0..17
+              FieldAccess@0..17
+                New@0..15
+                  New@0..3 "new"
+                  Whitespace@3..4 " "
+                  Identifier@4..9 "Point"
+                  LParen@9..10 "("
+                  Int@10..11
+                    Int@10..11 "1"
+                  Comma@11..12 ","
+                  Whitespace@12..13 " "
+                  Int@13..14
+                    Int@13..14 "2"
+                  RParen@14..15 ")"
+                Dot@15..16 "."
+                Identifier@16..17 "x"
+        "#]]);
+        expect_parser_err("struct Point { x int, x int }");
+        expect_parser_err("struct Point { x }");
+        expect_parser_err("new Point");
+        expect_parser_err("p.1");
+    }
+
+    #[test]
+    fn lambda_params() {
+        expect_parser("|x int, y| x + y", expect![[r#"
+            Root@0..16
+              Lambda@0..16
+                Pipe@0..1 "|"
+                Param@1..6
+                  Identifier@1..2 "x"
+                  Whitespace@2..3 " "
+                  Type@3..6
+                    TypeInt@3..6 "int"
+                Comma@6..7 ","
+                Whitespace@7..8 " "
+                Param@8..9
+                  Identifier@8..9 "y"
+                Pipe@9..10 "|"
+                Whitespace@10..11 " "
+                Binary@11..16
+                  Identifier@11..12
+                    Identifier@11..12 "x"
+                  Whitespace@12..13 " "
+                  Plus@13..14 "+"
+                  Whitespace@14..15 " "
+                  Identifier@15..16
+                    Identifier@15..16 "y"
+        "#]]);
+        expect_parser("pairs @map(|k str, v (int?)| v) ; |x| ", expect![[r#"
+            Root@0..38
+              Call@0..31
+                Identifier@0..5
+                  Identifier@0..5 "pairs"
+                Whitespace@5..6 " "
+                At@6..7 "@"
+                Identifier@7..10
+                  Identifier@7..10 "map"
+                LParen@10..11 "("
+                Lambda@11..30
+                  Pipe@11..12 "|"
+                  Param@12..17
+                    Identifier@12..13 "k"
+                    Whitespace@13..14 " "
+                    Type@14..17
+                      TypeString@14..17 "str"
+                  Comma@17..18 ","
+                  Whitespace@18..19 " "
+                  Param@19..27
+                    Identifier@19..20 "v"
+                    Whitespace@20..21 " "
+                    Type@21..27
+                      LParen@21..22 "("
+                      Type@22..26
+                        TypeInt@22..25 "int"
+                        Question@25..26 "?"
+                      RParen@26..27 ")"
+                  Pipe@27..28 "|"
+                  Whitespace@28..29 " "
+                  Identifier@29..30
+                    Identifier@29..30 "v"
+                RParen@30..31 ")"
+              Whitespace@31..32 " "
+              Semicolon@32..33 ";"
+              Whitespace@33..34 " "
+              Lambda@34..37
+                Pipe@34..35 "|"
+                Identifier@35..36
+                  Identifier@35..36 "x"
+                Pipe@36..37 "|"
+              Whitespace@37..38 " "
+        "#]]);
+        // a single untyped parameter needs a trailing comma, or it would be the body of an `it` lambda
+        expect_parser("|x,| x + 1", expect![[r#"
+            Root@0..10
+              Lambda@0..10
+                Pipe@0..1 "|"
+                Param@1..2
+                  Identifier@1..2 "x"
+                Comma@2..3 ","
+                Pipe@3..4 "|"
+                Whitespace@4..5 " "
+                Binary@5..10
+                  Identifier@5..6
+                    Identifier@5..6 "x"
+                  Whitespace@6..7 " "
+                  Plus@7..8 "+"
+                  Whitespace@8..9 " "
+                  Int@9..10
+                    Int@9..10 "1"
+        "#]]);
+        expect_parser_err("|x| x + 1");
+        expect_parser_err("xs @map(|x| (x))");
+        // applications chain after an applied lambda instead of joining its body
+        expect_parser("3 @|x int| x * 2 @g", expect![[r#"
+            Root@0..19
+              Call@0..19
+                Call@0..16
+                  Int@0..1
+                    Int@0..1 "3"
+                  Whitespace@1..2 " "
+                  At@2..3 "@"
+                  Lambda@3..16
+                    Pipe@3..4 "|"
+                    Param@4..9
+                      Identifier@4..5 "x"
+                      Whitespace@5..6 " "
+                      Type@6..9
+                        TypeInt@6..9 "int"
+                    Pipe@9..10 "|"
+                    Whitespace@10..11 " "
+                    Binary@11..16
+                      Identifier@11..12
+                        Identifier@11..12 "x"
+                      Whitespace@12..13 " "
+                      Star@13..14 "*"
+                      Whitespace@14..15 " "
+                      Int@15..16
+                        Int@15..16 "2"
+                Whitespace@16..17 " "
+                At@17..18 "@"
+                Identifier@18..19
+                  Identifier@18..19 "g"
+        "#]]);
+        assert!(Parser::new("3 @|x int| { x @f } @g").parse().is_ok());
+        expect_parser("3 @|x int| (x @f) @g", expect![[r#"
+            Root@0..20
+              Call@0..20
+                Call@0..17
+                  Int@0..1
+                    Int@0..1 "3"
+                  Whitespace@1..2 " "
+                  At@2..3 "@"
+                  Lambda@3..17
+                    Pipe@3..4 "|"
+                    Param@4..9
+                      Identifier@4..5 "x"
+                      Whitespace@5..6 " "
+                      Type@6..9
+                        TypeInt@6..9 "int"
+                    Pipe@9..10 "|"
+                    Whitespace@10..11 " "
+                    Group@11..17
+                      LParen@11..12 "("
+                      Call@12..16
+                        Identifier@12..13
+                          Identifier@12..13 "x"
+                        Whitespace@13..14 " "
+                        At@14..15 "@"
+                        Identifier@15..16
+                          Identifier@15..16 "f"
+                      RParen@16..17 ")"
+                Whitespace@17..18 " "
+                At@18..19 "@"
+                Identifier@19..20
+                  Identifier@19..20 "g"
+        "#]]);
+        expect_parser_err("|x int|");
+        expect_parser_err("|x int, 3| x");
+        expect_parser_err("|x int y| x");
+    }
+
+    #[test]
+    fn fn_types() {
+        expect_parser("fn(f fn(@int, ->int)) { 1 }", expect![[r#"
+            Root@0..27
+              FnDecl@0..27
+                Fn@0..2 "fn"
+                LParen@2..3 "("
+                Param@3..20
+                  Identifier@3..4 "f"
+                  Whitespace@4..5 " "
+                  Type@5..20
+                    Fn@5..7 "fn"
+                    LParen@7..8 "("
+                    Param@8..12
+                      At@8..9 "@"
+                      Type@9..12
+                        TypeInt@9..12 "int"
+                    Comma@12..13 ","
+                    Whitespace@13..14 " "
+                    Arrow@14..16 "->"
+                    Type@16..19
+                      TypeInt@16..19 "int"
+                    RParen@19..20 ")"
+                RParen@20..21 ")"
+                Whitespace@21..22 " "
+                Scope@22..27
+                  LBrace@22..23 "{"
+                  Whitespace@23..24 " "
+                  Int@24..25
+                    Int@24..25 "1"
+                  Whitespace@25..26 " "
+                  RBrace@26..27 "}"
+        "#]]);
+        expect_parser("fn(@fn(->int)) fn(@int ->int)? { 1 }", expect![[r#"
+            Root@0..36
+              FnDecl@0..36
+                Fn@0..2 "fn"
+                LParen@2..3 "("
+                Param@3..13
+                  At@3..4 "@"
+                  Type@4..13
+                    Fn@4..6 "fn"
+                    LParen@6..7 "("
+                    Arrow@7..9 "->"
+                    Type@9..12
+                      TypeInt@9..12 "int"
+                    RParen@12..13 ")"
+                RParen@13..14 ")"
+                Whitespace@14..15 " "
+                Type@15..30
+                  Fn@15..17 "fn"
+                  LParen@17..18 "("
+                  Param@18..22
+                    At@18..19 "@"
+                    Type@19..22
+                      TypeInt@19..22 "int"
+                  Whitespace@22..23 " "
+                  Arrow@23..25 "->"
+                  Type@25..28
+                    TypeInt@25..28 "int"
+                  RParen@28..29 ")"
+                  Question@29..30 "?"
+                Whitespace@30..31 " "
+                Scope@31..36
+                  LBrace@31..32 "{"
+                  Whitespace@32..33 " "
+                  Int@33..34
+                    Int@33..34 "1"
+                  Whitespace@34..35 " "
+                  RBrace@35..36 "}"
+        "#]]);
+        expect_parser("fn(f fn(@str, int, Point), g fn()) { 1 }", expect![[r#"
+            Root@0..40
+              FnDecl@0..40
+                Fn@0..2 "fn"
+                LParen@2..3 "("
+                Param@3..25
+                  Identifier@3..4 "f"
+                  Whitespace@4..5 " "
+                  Type@5..25
+                    Fn@5..7 "fn"
+                    LParen@7..8 "("
+                    Param@8..12
+                      At@8..9 "@"
+                      Type@9..12
+                        TypeString@9..12 "str"
+                    Comma@12..13 ","
+                    Whitespace@13..14 " "
+                    Param@14..17
+                      Type@14..17
+                        TypeInt@14..17 "int"
+                    Comma@17..18 ","
+                    Whitespace@18..19 " "
+                    Param@19..24
+                      Type@19..24
+                        Identifier@19..24 "Point"
+                    RParen@24..25 ")"
+                Comma@25..26 ","
+                Whitespace@26..27 " "
+                Param@27..33
+                  Identifier@27..28 "g"
+                  Whitespace@28..29 " "
+                  Type@29..33
+                    Fn@29..31 "fn"
+                    LParen@31..32 "("
+                    RParen@32..33 ")"
+                RParen@33..34 ")"
+                Whitespace@34..35 " "
+                Scope@35..40
+                  LBrace@35..36 "{"
+                  Whitespace@36..37 " "
+                  Int@37..38
+                    Int@37..38 "1"
+                  Whitespace@38..39 " "
+                  RBrace@39..40 "}"
+        "#]]);
+        expect_parser_err("fn(f fn(@int ->)) { 1 }");
+        expect_parser_err("fn(f fn(@int int)) { 1 }");
+        expect_parser_err("fn(f fn(->int, int)) { 1 }");
+        expect_parser_err("fn(f fn) { 1 }");
+    }
+
+    #[test]
+    fn raw_strings() {
+        expect_parser("$r\"\\d+\n\\w\"", expect![[r#"
+            Root@0..10
+              Print@0..10
+                Dollar@0..1 "$"
+                Str@1..10
+                  RawString@1..10 "r\"\\d+\n\\w\""
+        "#]]);
+    }
+
+    #[test]
+    fn type_of() {
+        expect_parser("typeof x[k] == \"int?\"", expect![[r#"
+            Root@0..21
+              Binary@0..21
+                Unary@0..11
+                  TypeOf@0..6 "typeof"
+                  Whitespace@6..7 " "
+                  Index@7..11
+                    Identifier@7..8
+                      Identifier@7..8 "x"
+                    LBracket@8..9 "["
+                    Identifier@9..10
+                      Identifier@9..10 "k"
+                    RBracket@10..11 "]"
+                Whitespace@11..12 " "
+                Equals@12..14 "=="
+                Whitespace@14..15 " "
+                Str@15..21
+                  String@15..21 "\"int?\""
+        "#]]);
+        expect_parser_err("typeof");
+    }
+
+    #[test]
+    fn ranges() {
+        expect_parser("var r = a + 1..n * 2; iter r { $it }", expect![[r#"
+            Root@0..36
+              VarDecl@0..20
+                Var@0..3 "var"
+                Whitespace@3..4 " "
+                Identifier@4..5
+                  Identifier@4..5 "r"
+                Whitespace@5..6 " "
+                Assign@6..7 "="
+                Whitespace@7..8 " "
+                Range@8..20
+                  Binary@8..13
+                    Identifier@8..9
+                      Identifier@8..9 "a"
+                    Whitespace@9..10 " "
+                    Plus@10..11 "+"
+                    Whitespace@11..12 " "
+                    Int@12..13
+                      Int@12..13 "1"
+                  Range@13..15 ".."
+                  Binary@15..20
+                    Identifier@15..16
+                      Identifier@15..16 "n"
+                    Whitespace@16..17 " "
+                    Star@17..18 "*"
+                    Whitespace@18..19 " "
+                    Int@19..20
+                      Int@19..20 "2"
+              Semicolon@20..21 ";"
+              Whitespace@21..22 " "
+              Iterate@22..36
+                Iter@22..26 "iter"
+                Whitespace@26..27 " "
+                Identifier@27..28
+                  Identifier@27..28 "r"
+                Whitespace@28..29 " "
+                Scope@29..36
+                  LBrace@29..30 "{"
+                  Whitespace@30..31 " "
+                  Print@31..34
+                    Dollar@31..32 "$"
+                    Identifier@32..34
+                      Identifier@32..34 "it"
+                  Whitespace@34..35 " "
+                  RBrace@35..36 "}"
+        "#]]);
+        expect_parser_err("1..2..3");
+        expect_parser_err("1..");
+    }
+
+    #[test]
+    fn casts() {
+        expect_parser("-x as int? else 0; a * b as float", expect![[r#"
+            Root@0..33
+              Binary@0..17
+                Cast@0..10
+                  Unary@0..2
+                    Minus@0..1 "-"
+                    Identifier@1..2
+                      Identifier@1..2 "x"
+                  Whitespace@2..3 " "
+                  As@3..5 "as"
+                  Whitespace@5..6 " "
+                  Type@6..10
+                    TypeInt@6..9 "int"
+                    Question@9..10 "?"
+                Whitespace@10..11 " "
+                Else@11..15 "else"
+                Whitespace@15..16 " "
+                Int@16..17
+                  Int@16..17 "0"
+              Semicolon@17..18 ";"
+              Whitespace@18..19 " "
+              Binary@19..33
+                Identifier@19..20
+                  Identifier@19..20 "a"
+                Whitespace@20..21 " "
+                Star@21..22 "*"
+                Whitespace@22..23 " "
+                Cast@23..33
+                  Identifier@23..24
+                    Identifier@23..24 "b"
+                  Whitespace@24..25 " "
+                  As@25..27 "as"
+                  Whitespace@27..28 " "
+                  Type@28..33
+                    TypeFloat@28..33 "float"
+        "#]]);
+        expect_parser_err("x as 3");
+        expect_parser_err("x as");
+    }
+
+    #[test]
+    fn empty_statements() {
+        expect_parser("", expect![[r#"
+            Root@0..0
+        "#]]);
+        expect_parser(";; {}; { ; }", expect![[r#"
+            Root@0..12
+              Semicolon@0..1 ";"
+              Semicolon@1..2 ";"
+              Whitespace@2..3 " "
+              Scope@3..5
+                LBrace@3..4 "{"
+                RBrace@4..5 "}"
+              Semicolon@5..6 ";"
+              Whitespace@6..7 " "
+              Scope@7..12
+                LBrace@7..8 "{"
+                Whitespace@8..9 " "
+                Semicolon@9..10 ";"
+                Whitespace@10..11 " "
+                RBrace@11..12 "}"
+        "#]]);
+        expect_parser("1;;2;", expect![[r#"
+            Root@0..5
+              Int@0..1
+                Int@0..1 "1"
+              Semicolon@1..2 ";"
+              Semicolon@2..3 ";"
+              Int@3..4
+                Int@3..4 "2"
+              Semicolon@4..5 ";"
+        "#]]);
+    }
+
+    #[test]
+    fn matches() {
+        expect_parser("match x { 1: \"one\", int?: \"maybe\", y: \"y\", _: no }", expect![[r#"
+            Root@0..50
+              Match@0..50
+                Match@0..5 "match"
+                Whitespace@5..6 " "
+                Identifier@6..7
+                  Identifier@6..7 "x"
+                Whitespace@7..8 " "
+                LBrace@8..9 "{"
+                Whitespace@9..10 " "
+                MatchArm@10..18
+                  Int@10..11
+                    Int@10..11 "1"
+                  Colon@11..12 ":"
+                  Whitespace@12..13 " "
+                  Str@13..18
+                    String@13..18 "\"one\""
+                Comma@18..19 ","
+                Whitespace@19..20 " "
+                MatchArm@20..33
+                  Type@20..24
+                    TypeInt@20..23 "int"
+                    Question@23..24 "?"
+                  Colon@24..25 ":"
+                  Whitespace@25..26 " "
+                  Str@26..33
+                    String@26..33 "\"maybe\""
+                Comma@33..34 ","
+                Whitespace@34..35 " "
+                MatchArm@35..41
+                  Identifier@35..36
+                    Identifier@35..36 "y"
+                  Colon@36..37 ":"
+                  Whitespace@37..38 " "
+                  Str@38..41
+                    String@38..41 "\"y\""
+                Comma@41..42 ","
+                Whitespace@42..43 " "
+                MatchArm@43..48
+                  DefaultKey@43..44
+                    Underscore@43..44 "_"
+                  Colon@44..45 ":"
+                  Whitespace@45..46 " "
+                  No@46..48
+                    No@46..48 "no"
+                Whitespace@48..49 " "
+                RBrace@49..50 "}"
+        "#]]);
+        expect_parser("match x { (a + 1): 2 }", expect![[r#"
+            Root@0..22
+              Match@0..22
+                Match@0..5 "match"
+                Whitespace@5..6 " "
+                Identifier@6..7
+                  Identifier@6..7 "x"
+                Whitespace@7..8 " "
+                LBrace@8..9 "{"
+                Whitespace@9..10 " "
+                MatchArm@10..20
+                  Group@10..17
+                    LParen@10..11 "("
+                    Binary@11..16
+                      Identifier@11..12
+                        Identifier@11..12 "a"
+                      Whitespace@12..13 " "
+                      Plus@13..14 "+"
+                      Whitespace@14..15 " "
+                      Int@15..16
+                        Int@15..16 "1"
+                    RParen@16..17 ")"
+                  Colon@17..18 ":"
+                  Whitespace@18..19 " "
+                  Int@19..20
+                    Int@19..20 "2"
+                Whitespace@20..21 " "
+                RBrace@21..22 "}"
+        "#]]);
+        expect_parser_err("match x { }");
+        expect_parser_err("match x { _: 1, 2: 2 }");
+        expect_parser_err("match x { 1 }");
+        expect_parser_err("match { 1: 1 }");
+    }
+
+    #[test]
+    fn default_params() {
+        expect_parser("fn(@int, step int = 1, by int = step * 2) int { it }", expect![[r#"
+            Root@0..52
+              FnDecl@0..52
+                Fn@0..2 "fn"
+                LParen@2..3 "("
+                Param@3..7
+                  At@3..4 "@"
+                  Type@4..7
+                    TypeInt@4..7 "int"
+                Comma@7..8 ","
+                Whitespace@8..9 " "
+                Param@9..21
+                  Identifier@9..13 "step"
+                  Whitespace@13..14 " "
+                  Type@14..17
+                    TypeInt@14..17 "int"
+                  Whitespace@17..18 " "
+                  Assign@18..19 "="
+                  Whitespace@19..20 " "
+                  Int@20..21
+                    Int@20..21 "1"
+                Comma@21..22 ","
+                Whitespace@22..23 " "
+                Param@23..40
+                  Identifier@23..25 "by"
+                  Whitespace@25..26 " "
+                  Type@26..29
+                    TypeInt@26..29 "int"
+                  Whitespace@29..30 " "
+                  Assign@30..31 "="
+                  Whitespace@31..32 " "
+                  Binary@32..40
+                    Identifier@32..36
+                      Identifier@32..36 "step"
+                    Whitespace@36..37 " "
+                    Star@37..38 "*"
+                    Whitespace@38..39 " "
+                    Int@39..40
+                      Int@39..40 "2"
+                RParen@40..41 ")"
+                Whitespace@41..42 " "
+                Type@42..45
+                  TypeInt@42..45 "int"
+                Whitespace@45..46 " "
+                Scope@46..52
+                  LBrace@46..47 "{"
+                  Whitespace@47..48 " "
+                  Identifier@48..50
+                    Identifier@48..50 "it"
+                  Whitespace@50..51 " "
+                  RBrace@51..52 "}"
+        "#]]);
+        expect_parser_err("fn(@int = 1) { it }");
+        expect_parser_err("fn(a int = 1, b int) { a }");
+        expect_parser_err("fn(a int =) { a }");
+        expect_parser_err_at("fn(a int = 1, bb int) { a }", 14..16);
+    }
+
+    #[test]
+    fn variadic_params() {
+        expect_parser("fn(@str, sep str = \",\", parts... str) str { it }", expect![[r#"
+            Root@0..48
+              FnDecl@0..48
+                Fn@0..2 "fn"
+                LParen@2..3 "("
+                Param@3..7
+                  At@3..4 "@"
+                  Type@4..7
+                    TypeString@4..7 "str"
+                Comma@7..8 ","
+                Whitespace@8..9 " "
+                Param@9..22
+                  Identifier@9..12 "sep"
+                  Whitespace@12..13 " "
+                  Type@13..16
+                    TypeString@13..16 "str"
+                  Whitespace@16..17 " "
+                  Assign@17..18 "="
+                  Whitespace@18..19 " "
+                  Str@19..22
+                    String@19..22 "\",\""
+                Comma@22..23 ","
+                Whitespace@23..24 " "
+                Param@24..36
+                  Identifier@24..29 "parts"
+                  Ellipsis@29..32 "..."
+                  Whitespace@32..33 " "
+                  Type@33..36
+                    TypeString@33..36 "str"
+                RParen@36..37 ")"
+                Whitespace@37..38 " "
+                Type@38..41
+                  TypeString@38..41 "str"
+                Whitespace@41..42 " "
+                Scope@42..48
+                  LBrace@42..43 "{"
+                  Whitespace@43..44 " "
+                  Identifier@44..46
+                    Identifier@44..46 "it"
+                  Whitespace@46..47 " "
+                  RBrace@47..48 "}"
+        "#]]);
+        expect_parser_err_at("fn(rest... int, last int) { 1 }", 16..20);
+        expect_parser_err("fn(rest... int = 1) { 1 }");
+        expect_parser_err("fn(@... int) { 1 }");
+    }
+}
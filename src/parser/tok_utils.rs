@@ -3,9 +3,65 @@ use crate::lexer::tok::Tok;
 impl Tok {
     pub fn precedence(&self) -> u8 {
         match self {
-            Tok::Plus | Tok::Minus => 10,
-            Tok::Star | Tok::Slash => 20,
-            _ => unimplemented!("must assign precedence to tok {:?}", self),
+            Tok::Assign
+            | Tok::PlusAssign
+            | Tok::MinusAssign
+            | Tok::StarAssign
+            | Tok::SlashAssign
+            | Tok::CaretAssign
+            | Tok::ModuloAssign => 1,
+            Tok::Else => 2,
+            Tok::Or | Tok::Xor => 3,
+            Tok::And => 4,
+            Tok::GreaterThan
+            | Tok::LessThan
+            | Tok::GreaterThanEq
+            | Tok::LessThanEq
+            | Tok::Equals
+            | Tok::NotEquals => 5,
+            Tok::Range => 6,
+            Tok::BitOr => 7,
+            Tok::BitXor => 8,
+            Tok::BitAnd => 9,
+            Tok::ShiftLeft | Tok::ShiftRight => 10,
+            Tok::Plus | Tok::Minus => 15,
+            Tok::Star | Tok::Slash | Tok::Modulo => 20,
+            Tok::As => 25,
+            Tok::Caret => 40,
+            // applications chain left to right: `3 @double @add(1)` is `(3 @double) @add(1)`
+            Tok::At => 100,
+            // postfix `?` and `!` apply only to the operand right before them: `a + b?` is `a + (b?)`
+            Tok::Question | Tok::Bang => 110,
+            Tok::LBracket | Tok::Dot => 120,
+            // anything that can't continue an expression terminates it
+            _ => 0,
         }
     }
-}
\ No newline at end of file
+
+    /// whether a type annotation can begin with this token
+    pub fn starts_type(&self) -> bool {
+        matches!(self,
+            Tok::TypeInt
+            | Tok::TypeFloat
+            | Tok::TypeString
+            | Tok::TypeBool
+            | Tok::TypeChar
+            | Tok::Identifier
+            | Tok::LParen
+            | Tok::Fn)
+    }
+
+    /// whether this token is a literal, a name or a group, that can only begin an operand
+    pub fn starts_operand(&self) -> bool {
+        matches!(self,
+            Tok::Identifier
+            | Tok::Int
+            | Tok::Float
+            | Tok::String
+            | Tok::RawString
+            | Tok::Bool
+            | Tok::Char
+            | Tok::No
+            | Tok::LParen)
+    }
+}
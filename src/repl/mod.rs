@@ -4,11 +4,16 @@ use rustyline::DefaultEditor;
 use std::io;
 use std::io::Write;
 use std::string::String;
+use crate::config::Config;
 use crate::parser::Parser;
 
-pub fn serve_repl() {
+pub fn serve_repl(config: &Config) {
     let mut stdout = io::stdout();
     let mut input_reader = DefaultEditor::new().unwrap();
+    if let Some(history) = &config.repl.history {
+        // there's no history to load on the first session
+        let _ = input_reader.load_history(history);
+    }
     let mut source = String::new();
 
     loop {
@@ -23,27 +28,62 @@ pub fn serve_repl() {
         };
 
         input_reader.add_history_entry(input.as_str()).unwrap();
-        match input.trim() {
+        if let Some(history) = &config.repl.history {
+            if let Err(err) = input_reader.save_history(history) {
+                log::warn!("could not save the history to {}: {err}", history.display());
+            }
+        }
+        let (input, pasted) = match input.trim() {
             "exit" => break,
             "help" => {
                 println!(r#"
     {} - terminates the REPL session
     {} - shows this message
+    {} - reads lines until a lone `.`, then evaluates them together as in a file
 "#,
                          "exit".underline(),
-                         "help".underline());
+                         "help".underline(),
+                         ":paste".underline());
                 continue;
             }
-            _ => {}
-        }
+            ":paste" => match read_paste(&mut input_reader) {
+                Some(block) => (block, true),
+                None => continue,
+            },
+            _ => (input, false),
+        };
 
+        // the session is shown as a single file, which this input is the end of
+        let offset = source.len();
         source.push_str(&input);
         source.push('\n');
         let sf = SimpleFile::new("repl.rs", &source);
 
-        let mut parser = Parser::new(&*input);
-        if let Err(err) = parser.parse() { err.emit(&sf); };
+        // a pasted block is parsed as a file is, so its newlines never end a statement
+        let mut parser = Parser::new(&input).infer_semicolons(!pasted);
+        match parser.parse() {
+            Ok(()) => println!("{:#?}", parser.into_ast()),
+            Err(err) => err.shifted(offset).emit(&sf),
+        }
+    }
+}
 
-        println!("{:#?}", parser.to_ast())
+/// reads a block verbatim, up to a line that only holds a `.`.
+/// the block ends up in a single parse, so it can span several statements.
+fn read_paste(input_reader: &mut DefaultEditor) -> Option<String> {
+    println!("(pasting, end with a lone `.`)");
+    let mut block = String::new();
+    loop {
+        match input_reader.readline(".. ") {
+            Ok(line) if line.trim() == "." => return Some(block),
+            Ok(line) => {
+                block.push_str(&line);
+                block.push('\n');
+            }
+            Err(_) => {
+                eprintln!("Paste interrupted.");
+                return None;
+            }
+        }
     }
-}
\ No newline at end of file
+}
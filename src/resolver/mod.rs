@@ -0,0 +1,294 @@
+use crate::errors::err::LanguloErr;
+use crate::lexer::tok::Tok;
+use crate::syntax_tree::expr::Expr;
+use crate::syntax_tree::lang::{Langulo, LanguloKind, LanguloSyntaxNode};
+use logos::Span;
+use rowan::{NodeOrToken, SyntaxToken};
+use std::collections::HashMap;
+
+/// checks that every variable is declared before it's used, and at most once per scope.
+/// a declaration in an inner scope shadows the outer ones until the scope closes.
+pub fn resolve(root: &LanguloSyntaxNode) -> Result<SymbolTable, LanguloErr> {
+    let mut resolver = Resolver { scopes: Vec::new(), symbols: SymbolTable::default() };
+    resolver.node(root)?;
+    Ok(resolver.symbols)
+}
+
+/// what the resolver found out about the variables of a program, for the stages after it
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    /// every declaration, in the order they appear
+    pub declarations: Vec<Declaration>,
+    /// the declaration that each use of a variable refers to, by the span of the use
+    pub uses: HashMap<Span, usize>,
+}
+
+#[derive(Debug)]
+pub struct Declaration {
+    pub name: String,
+    /// where the name is declared. implicit bindings such as `it` have no span
+    pub span: Option<Span>,
+}
+
+struct Resolver {
+    /// the declarations made so far in each open scope, innermost last
+    scopes: Vec<Vec<usize>>,
+    symbols: SymbolTable,
+}
+
+impl Resolver {
+    fn node(&mut self, node: &LanguloSyntaxNode) -> Result<(), LanguloErr> {
+        let LanguloKind::Node(expr) = node.kind() else { return Ok(()) };
+        match expr {
+            Expr::Root | Expr::Scope => self.scoped(&[], |this| this.children(node)),
+            Expr::Identifier => {
+                let name = node.text().to_string();
+                let Some(declaration) = self.lookup(&name) else {
+                    return Err(LanguloErr::semantic_at(
+                        &format!("Variable {} isn't declared at this point", name), &span_of(node),
+                    ));
+                };
+                self.symbols.uses.insert(span_of(node), declaration);
+                Ok(())
+            }
+            Expr::VarDecl => {
+                let mut children = node.children();
+                let name = children.next().expect("a declaration always has a name");
+                for child in children {
+                    self.node(&child)?;
+                }
+                self.declare(&name.text().to_string(), span_of(&name))
+            }
+            Expr::FnDecl => {
+                if let Some(name) = identifier_token(node) {
+                    self.declare(name.text(), name.text_range().into())?;
+                }
+                // the principal parameter has no name, and is referred to as `it`
+                self.scoped(&["it"], |this| this.params_then_children(node))
+            }
+            Expr::Lambda => {
+                let implicit: &[&str] = if has_params(node) { &[] } else { &["it"] };
+                self.scoped(implicit, |this| this.params_then_children(node))
+            }
+            Expr::Iterate => {
+                let mut children = node.children();
+                let source = children.next().expect("an iteration always has a source");
+                self.node(&source)?;
+                self.scoped(&["it"], |this| children.try_for_each(|child| this.node(&child)))
+            }
+            // `[x: x * x iter source]` binds each element to the name before the colon,
+            // and `[it * 2 iter source]` to `it`, as an iteration does. the binding is only
+            // visible within the entry: the source is resolved outside of it
+            Expr::Comprehension => {
+                let source = node.last_child().expect("a comprehension always has a source");
+                self.node(&source)?;
+                let binding = node.first_child().filter(is_key_name);
+                let implicit: &[&str] = if binding.is_some() { &[] } else { &["it"] };
+                self.scoped(implicit, |this| {
+                    if let Some(binding) = &binding {
+                        this.declare(&binding.text().to_string(), span_of(binding))?;
+                    }
+                    node.children()
+                        .filter(|child| Some(child) != binding.as_ref() && child != &source)
+                        .try_for_each(|child| this.node(&child))
+                })
+            }
+            // `[a: 1]` names its key, while `[a]` and `[k: 1]` within an index hold values
+            Expr::Table => node.children()
+                .filter(|child| !is_key_name(child))
+                .try_for_each(|child| self.node(&child)),
+            // the name of a function that is called is looked up among the functions instead
+            Expr::Call => node.children()
+                .filter(|child| !follows_at(child))
+                .try_for_each(|child| self.node(&child)),
+            Expr::NamedArg => node.children().skip(1).try_for_each(|child| self.node(&child)),
+            Expr::StructDecl | Expr::Type => Ok(()),
+            _ => self.children(node),
+        }
+    }
+
+    fn children(&mut self, node: &LanguloSyntaxNode) -> Result<(), LanguloErr> {
+        node.children().try_for_each(|child| self.node(&child))
+    }
+
+    fn params_then_children(&mut self, node: &LanguloSyntaxNode) -> Result<(), LanguloErr> {
+        for param in node.children().filter(|child| child.kind() == LanguloKind::Node(Expr::Param)) {
+            // a default value can refer to the parameters before it
+            self.children(&param)?;
+            if let Some(name) = identifier_token(&param) {
+                self.declare(name.text(), name.text_range().into())?;
+            }
+        }
+        node.children()
+            .filter(|child| child.kind() != LanguloKind::Node(Expr::Param))
+            .try_for_each(|child| self.node(&child))
+    }
+
+    fn scoped(
+        &mut self,
+        implicit: &[&str],
+        inside: impl FnOnce(&mut Self) -> Result<(), LanguloErr>,
+    ) -> Result<(), LanguloErr> {
+        self.scopes.push(Vec::new());
+        for name in implicit {
+            self.add_declaration(name, None);
+        }
+        let result = inside(self);
+        self.scopes.pop();
+        result
+    }
+
+    fn declare(&mut self, name: &str, span: Span) -> Result<(), LanguloErr> {
+        let scope = self.scopes.last().expect("declarations always happen within a scope");
+        let declarations = &self.symbols.declarations;
+        if let Some(&previous) = scope.iter().find(|&&index| declarations[index].name == name) {
+            let err = LanguloErr::semantic_at(
+                &format!("Variable {} is already declared in this scope", name), &span,
+            );
+            return Err(match &declarations[previous].span {
+                Some(previous) => err.with_note_at("previously declared here", previous),
+                None => err,
+            });
+        }
+        self.add_declaration(name, Some(span));
+        Ok(())
+    }
+
+    fn add_declaration(&mut self, name: &str, span: Option<Span>) {
+        let scope = self.scopes.last_mut().expect("declarations always happen within a scope");
+        scope.push(self.symbols.declarations.len());
+        self.symbols.declarations.push(Declaration { name: name.to_string(), span });
+    }
+
+    /// the declaration that `name` refers to at this point, starting from the innermost scope
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev()
+            .flat_map(|scope| scope.iter().rev())
+            .copied()
+            .find(|&index| self.symbols.declarations[index].name == name)
+    }
+}
+
+fn span_of(node: &LanguloSyntaxNode) -> Span {
+    node.text_range().into()
+}
+
+fn identifier_token(node: &LanguloSyntaxNode) -> Option<SyntaxToken<Langulo>> {
+    node.children_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+        .find(|token| token.kind() == LanguloKind::Token(Tok::Identifier))
+}
+
+fn has_params(node: &LanguloSyntaxNode) -> bool {
+    node.children().any(|child| child.kind() == LanguloKind::Node(Expr::Param))
+}
+
+/// the kind of the first sibling after `node` that isn't trivia
+fn next_significant(node: &LanguloSyntaxNode) -> Option<LanguloKind> {
+    std::iter::successors(node.next_sibling_or_token(), |element| element.next_sibling_or_token())
+        .map(|element| element.kind())
+        .find(|kind| !is_trivia(kind))
+}
+
+/// the kind of the last sibling before `node` that isn't trivia
+fn prev_significant(node: &LanguloSyntaxNode) -> Option<LanguloKind> {
+    std::iter::successors(node.prev_sibling_or_token(), |element| element.prev_sibling_or_token())
+        .map(|element| element.kind())
+        .find(|kind| !is_trivia(kind))
+}
+
+fn is_trivia(kind: &LanguloKind) -> bool {
+    matches!(kind, LanguloKind::Token(Tok::Whitespace | Tok::Comment))
+}
+
+fn is_key_name(node: &LanguloSyntaxNode) -> bool {
+    node.kind() == LanguloKind::Node(Expr::Identifier)
+        && matches!(next_significant(node), Some(LanguloKind::Token(Tok::Colon | Tok::Iter)))
+}
+
+fn follows_at(node: &LanguloSyntaxNode) -> bool {
+    node.kind() == LanguloKind::Node(Expr::Identifier)
+        && prev_significant(node) == Some(LanguloKind::Token(Tok::At))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn resolve_source(input: &str) -> Result<SymbolTable, LanguloErr> {
+        let mut parser = Parser::new(input);
+        parser.parse().expect("failed to parse");
+        resolve(&parser.into_ast())
+    }
+
+    fn expect_resolved(input: &str) {
+        if let Err(err) = resolve_source(input) {
+            panic!("failed to resolve {input:?}: {err:?}");
+        }
+    }
+
+    fn expect_unresolved(input: &str) {
+        assert!(resolve_source(input).is_err(), "{input:?} should not resolve");
+    }
+
+    #[test]
+    fn symbol_table() {
+        let symbols = resolve_source("var x = 1; { var x = x; x }; x").expect("failed to resolve");
+        let declared: Vec<_> = symbols.declarations.iter()
+            .map(|declaration| (declaration.name.as_str(), declaration.span.clone()))
+            .collect();
+        assert_eq!(declared, [("x", Some(4..5)), ("x", Some(17..18))]);
+        assert_eq!(symbols.uses, HashMap::from([(21..22, 0), (24..25, 1), (29..30, 0)]));
+
+        let symbols = resolve_source("fn f(@int) { it }").expect("failed to resolve");
+        let it = symbols.uses[&(13..15)];
+        assert_eq!(symbols.declarations[it].name, "it");
+        assert_eq!(symbols.declarations[it].span, None);
+    }
+
+    #[test]
+    fn declarations() {
+        expect_resolved("var x = 1; x = x + 1; var y = x");
+        expect_unresolved("x");
+        expect_unresolved("x = 1");
+        expect_unresolved("x; var x = 1");
+        expect_unresolved("var x = x");
+        expect_unresolved("var x = 1; var x = 2");
+    }
+
+    #[test]
+    fn shadowing() {
+        expect_resolved("var x = 1; { var x = x; x }; x");
+        expect_resolved("var x = 1; fn f(x int) { var y = x; y }");
+        expect_unresolved("{ var y = 1 }; y");
+        expect_unresolved("fn f(x int, x int) { 1 }");
+        expect_resolved("fn f(x int, y int = x * 2) { y }");
+        expect_resolved("fn f(x int, rest... int) { rest @push(x) }");
+        expect_unresolved("fn f(x int = y, y int = 1) { x }");
+        expect_unresolved("fn f() { var z = 1; z }; z");
+    }
+
+    #[test]
+    fn implicit_bindings() {
+        expect_resolved("var xs = [1, 2]; xs @map(|it * 2|); iter xs { $it }");
+        expect_resolved("fn double(@int) int { it * 2 }; 3 @double");
+        expect_resolved("3 @ double @ |it|");
+        expect_resolved("|a int, b| a + b; [x: x * x iter 1..10]");
+        expect_resolved("var k = 1; var t = [a: k, _: 0]; t[k]");
+        expect_unresolved("it");
+        expect_unresolved("|a int| it");
+        expect_unresolved("[x: y iter 1..10]");
+        expect_unresolved("[x: x iter x]");
+        expect_resolved("var xs = [1, 2]; [it * 2 iter xs]; [it: it * it iter xs]");
+        expect_unresolved("[x: it iter 1..10]");
+        expect_unresolved("[it * 2 iter it]");
+    }
+
+    #[test]
+    fn calls_and_structs() {
+        expect_resolved("struct P { x int }; var p = new P(x: 1); p.x @show(width: p.x)");
+        expect_unresolved("1 @show(width: w)");
+        expect_unresolved("q.x");
+    }
+}
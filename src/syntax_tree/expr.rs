@@ -1,17 +1,48 @@
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::ToPrimitive;
-use rowan::{GreenNode, NodeOrToken, SyntaxKind};
+use rowan::SyntaxKind;
 
 #[derive(FromPrimitive, ToPrimitive, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Expr {
     Root,
     Identifier,
     Literal,
-    Whitespace,
     Binary,
     Unary,
     Int,
-    Comment,
+    Float,
+    Table,
+    Comprehension,
+    Call,
+    Str,
+    Scope,
+    TestBlock,
+    No,
+    Print,
+    Index,
+    FnDecl,
+    Param,
+    Type,
+    NamedArg,
+    Lambda,
+    While,
+    Iterate,
+    VarDecl,
+    Assign,
+    DefaultKey,
+    Group,
+    Optional,
+    Unwrap,
+    StructDecl,
+    Field,
+    New,
+    FieldAccess,
+    Range,
+    Cast,
+    Match,
+    MatchArm,
+    Bool,
+    Char,
 }
 
 impl From<Expr> for SyntaxKind {
@@ -19,9 +50,3 @@ impl From<Expr> for SyntaxKind {
         SyntaxKind(value.to_u16().unwrap())
     }
 }
-
-impl From<NodeOrToken<GreenNode, GreenNode>> for Expr {
-    fn from(value: NodeOrToken<GreenNode, GreenNode>) -> Self {
-        value.into()
-    }
-}
\ No newline at end of file
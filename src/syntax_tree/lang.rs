@@ -1,20 +1,55 @@
+use crate::lexer::tok::Tok;
 use crate::syntax_tree::expr::Expr;
-use num_traits::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
 use rowan::{Language, SyntaxKind, SyntaxNode};
+use std::fmt;
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum Langulo {}
 
+/// nodes of the tree are expressions, while its tokens keep the kind the lexer gave them,
+/// so that the source (trivia included) can be reconstructed exactly from the tree
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum LanguloKind {
+    Node(Expr),
+    Token(Tok),
+}
+
+/// raw kinds from this one onwards are tokens
+const TOKEN_OFFSET: u16 = 1 << 15;
+
+impl From<Tok> for SyntaxKind {
+    fn from(value: Tok) -> Self {
+        SyntaxKind(TOKEN_OFFSET + value as u16)
+    }
+}
+
+impl fmt::Debug for LanguloKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LanguloKind::Node(expr) => expr.fmt(f),
+            LanguloKind::Token(tok) => tok.fmt(f),
+        }
+    }
+}
+
 impl Language for Langulo {
-    type Kind = Expr;
+    type Kind = LanguloKind;
 
     fn kind_from_raw(raw: SyntaxKind) -> Self::Kind {
-        Self::Kind::from_i16(raw.0 as i16).unwrap()
+        if raw.0 >= TOKEN_OFFSET {
+            LanguloKind::Token(Tok::from_u16(raw.0 - TOKEN_OFFSET).unwrap())
+        } else {
+            LanguloKind::Node(Expr::from_u16(raw.0).unwrap())
+        }
     }
 
     fn kind_to_raw(kind: Self::Kind) -> SyntaxKind {
-        SyntaxKind(kind.to_u16().unwrap())
+        match kind {
+            LanguloKind::Node(expr) => expr.into(),
+            LanguloKind::Token(tok) => tok.into(),
+        }
     }
 }
 
-pub type LanguloSyntaxNode = SyntaxNode<Langulo>;
\ No newline at end of file
+pub type LanguloSyntaxNode = SyntaxNode<Langulo>;